@@ -0,0 +1,276 @@
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use perfect_reconstructibility::{
+	directed::{
+		dominator_finder::{Algorithm, DominatorFinder},
+		strongly_connected_finder::StronglyConnectedFinder,
+	},
+	generator::{Generator, Parameters},
+	list::{Instruction, List},
+	nodes::Nodes,
+	set::Set,
+	structurer::{branch, repeat},
+};
+
+struct Counter;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counter {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout);
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+
+		System.realloc(ptr, layout, new_size)
+	}
+}
+
+#[global_allocator]
+static GLOBAL: Counter = Counter;
+
+const SIZES: [usize; 3] = [64, 512, 4096];
+
+fn with_simple(len: usize) -> List {
+	let mut list = List::with_capacity(len);
+
+	for _ in 0..len {
+		list.add_instruction(Instruction::Simple);
+	}
+
+	list
+}
+
+fn chain(len: usize) -> List {
+	let mut list = with_simple(len);
+
+	for id in 1..len {
+		list.add_link(id - 1, id);
+	}
+
+	list
+}
+
+// A head selects between many cases, each continuing to one of a few tails.
+fn switch(len: usize) -> List {
+	let mut list = with_simple(len + 5);
+	let tails = [len + 1, len + 2, len + 3];
+
+	for case in 1..=len {
+		list.add_link(0, case);
+		list.add_link(case, tails[case % tails.len()]);
+	}
+
+	for tail in tails {
+		list.add_link(tail, len + 4);
+	}
+
+	list
+}
+
+// Loops nested an eighth of `len` deep, with an exit out of every sixteenth level.
+fn nest(len: usize) -> List {
+	let depth = len / 8;
+	let body = depth + 1;
+	let exit = 2 * depth + 2;
+	let mut list = chain(exit + 1);
+
+	for level in 1..=depth {
+		let latch = body + level;
+
+		list.add_link(latch, body - level);
+
+		if level % 16 == 0 {
+			list.add_link(body - level, exit);
+		}
+	}
+
+	list
+}
+
+// A ring of nodes linked both ways, entered from many places.
+fn mesh(len: usize) -> List {
+	let exit = len + 1;
+	let mut list = with_simple(len + 2);
+
+	for id in 1..=len {
+		let next = id % len + 1;
+
+		list.add_link(id, next);
+		list.add_link(next, id);
+
+		if id % 4 == 0 {
+			list.add_link(0, id);
+			list.add_link(id, exit);
+		}
+	}
+
+	list.add_link(0, 1);
+
+	list
+}
+
+// A random graph with a few irreducible loops, from a fixed seed.
+fn random(len: usize) -> List {
+	let parameters = Parameters {
+		nodes: len,
+		back_edges: 1.0 / 12.0,
+		max_degree: 3,
+		irreducibility: 0.25,
+		exits: 1 + len / 64,
+	};
+
+	Generator::new(0x2545_F491_4F6C_DD1D)
+		.run(&parameters)
+		.unwrap()
+}
+
+type Family = fn(usize) -> List;
+
+const FAMILIES: [(&str, Family); 5] = [
+	("chain", chain),
+	("switch", switch),
+	("nest", nest),
+	("mesh", mesh),
+	("random", random),
+];
+
+// Only the loops are restructured, so that the branch restructurers can be measured
+// on their own.
+fn structured(list: &List) -> (List, Set) {
+	let mut list = list.clone();
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+
+	(list, set)
+}
+
+fn report(name: &str, len: usize, list: &List) {
+	let before = ALLOCATIONS.load(Ordering::Relaxed);
+	let (mut structured, mut set) = structured(list);
+	let middle = ALLOCATIONS.load(Ordering::Relaxed);
+	let repetitions = structured.len() - list.len();
+
+	branch::Bulk::new().run(&mut structured, &mut set, 0);
+
+	let after = ALLOCATIONS.load(Ordering::Relaxed);
+	let branches = structured.len() - list.len() - repetitions;
+
+	println!(
+		"{name}/{len}: {} nodes, {repetitions} + {branches} synthetic nodes, {} + {} allocations",
+		list.len(),
+		middle - before,
+		after - middle,
+	);
+}
+
+fn bench_structurers(c: &mut Criterion) {
+	for (name, family) in FAMILIES {
+		for len in SIZES {
+			report(name, len, &family(len));
+		}
+	}
+
+	let mut group = c.benchmark_group("repeat::Bulk");
+
+	for (name, family) in FAMILIES {
+		for len in SIZES {
+			let list = family(len);
+
+			group.bench_with_input(BenchmarkId::new(name, len), &list, |b, list| {
+				b.iter_batched_ref(
+					|| (list.clone(), list.ids()),
+					|(list, set)| repeat::Bulk::new().run(list, set),
+					BatchSize::LargeInput,
+				);
+			});
+		}
+	}
+
+	group.finish();
+
+	let mut group = c.benchmark_group("branch::Bulk");
+
+	for (name, family) in FAMILIES {
+		for len in SIZES {
+			let list = structured(&family(len));
+
+			group.bench_with_input(BenchmarkId::new(name, len), &list, |b, list| {
+				b.iter_batched_ref(
+					|| list.clone(),
+					|(list, set)| branch::Bulk::new().run(list, set, 0),
+					BatchSize::LargeInput,
+				);
+			});
+
+			let id = BenchmarkId::new(format!("{name}/recomputed"), len);
+
+			group.bench_with_input(id, &list, |b, list| {
+				b.iter_batched_ref(
+					|| list.clone(),
+					|(list, set)| branch::Bulk::new().run_recomputed(list, set, 0),
+					BatchSize::LargeInput,
+				);
+			});
+		}
+	}
+
+	group.finish();
+}
+
+fn bench_directed(c: &mut Criterion) {
+	let mut group = c.benchmark_group("DominatorFinder");
+
+	for (name, family) in FAMILIES {
+		for len in SIZES {
+			let list = family(len);
+			let set = list.ids();
+
+			for (algorithm, label) in [
+				(Algorithm::Iterative, "iterative"),
+				(Algorithm::SemiNca, "semi_nca"),
+			] {
+				let id = BenchmarkId::new(format!("{name}/{label}"), len);
+				let mut dominator_finder = DominatorFinder::new();
+
+				group.bench_with_input(id, &list, |b, list| {
+					b.iter(|| dominator_finder.run_with(list, set.ones(), 0, algorithm));
+				});
+			}
+		}
+	}
+
+	group.finish();
+
+	let mut group = c.benchmark_group("StronglyConnectedFinder");
+
+	for (name, family) in FAMILIES {
+		for len in SIZES {
+			let list = family(len);
+			let set = list.ids();
+			let mut strongly_connected_finder = StronglyConnectedFinder::new();
+
+			group.bench_with_input(BenchmarkId::new(name, len), &list, |b, list| {
+				b.iter(|| strongly_connected_finder.run(list, set.as_slice(), drop));
+			});
+		}
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, bench_structurers, bench_directed);
+criterion_main!(benches);
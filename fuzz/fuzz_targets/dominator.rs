@@ -0,0 +1,93 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use perfect_reconstructibility::{
+	directed::{
+		dominator_finder::{Algorithm, DominatorFinder},
+		dominator_tree::DominatorTree,
+	},
+	list::List,
+	nodes::Successors,
+	set::Set,
+};
+
+// Finds the nodes of the set reached from the start without passing the removed one
+fn find_reached(list: &List, set: &Set, start: usize, removed: usize) -> Vec<bool> {
+	let mut reached = vec![false; list.len()];
+	let mut stack = vec![start];
+
+	while let Some(id) = stack.pop() {
+		if id == removed || !set[id] || std::mem::replace(&mut reached[id], true) {
+			continue;
+		}
+
+		stack.extend(list.successors(id));
+	}
+
+	reached
+}
+
+// A node strictly dominates another if removing it leaves the other unreached
+fn find_dominators(list: &List, set: &Set, start: usize, reached: &[bool]) -> Vec<Vec<usize>> {
+	let mut dominators = vec![Vec::new(); list.len()];
+
+	for dominator in (0..list.len()).filter(|&id| reached[id]) {
+		let without = find_reached(list, set, start, dominator);
+
+		for id in (0..list.len()).filter(|&id| reached[id] && id != dominator && !without[id]) {
+			dominators[id].push(dominator);
+		}
+	}
+
+	dominators
+}
+
+fuzz_target!(|input: (List, u8, Vec<bool>)| {
+	let (list, start, mask) = input;
+	let start = usize::from(start) % list.len();
+	let set: Set = (0..list.len())
+		.filter(|&id| id == start || mask.get(id).copied().unwrap_or(true))
+		.collect();
+
+	let mut iterative = DominatorFinder::new();
+	let mut semi_nca = DominatorFinder::new();
+	let mut tree = DominatorTree::new();
+
+	iterative.run_with(&list, set.ones(), start, Algorithm::Iterative);
+	semi_nca.run_with(&list, set.ones(), start, Algorithm::SemiNca);
+	tree.load(&iterative);
+
+	assert_eq!(iterative.ids(), semi_nca.ids());
+
+	let reached = find_reached(&list, &set, start, usize::MAX);
+	let dominators = find_dominators(&list, &set, start, &reached);
+
+	let mut ids = iterative.ids().to_vec();
+
+	ids.sort_unstable();
+
+	assert!(ids
+		.iter()
+		.copied()
+		.eq((0..list.len()).filter(|&id| reached[id])));
+
+	for id in 0..list.len() {
+		// The immediate dominator is the strict dominator the others also dominate
+		let expected = dominators[id]
+			.iter()
+			.copied()
+			.find(|&dominator| dominators[dominator].len() + 1 == dominators[id].len());
+
+		assert_eq!(iterative.dominator(id), expected, "dominator of {id}");
+		assert_eq!(semi_nca.dominator(id), expected, "dominator of {id}");
+	}
+
+	for dominator in ids.iter().copied() {
+		for id in ids.iter().copied() {
+			let expected = dominator == id || dominators[id].contains(&dominator);
+
+			assert_eq!(iterative.dominates(dominator, id), expected);
+			assert_eq!(tree.dominates(dominator, id), expected);
+		}
+	}
+});
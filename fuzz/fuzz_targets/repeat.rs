@@ -1,10 +1,7 @@
 #![no_main]
 
 use libfuzzer_sys::fuzz_target;
-use list::List;
-use perfect_reconstructibility::structurer::repeat;
-
-mod list;
+use perfect_reconstructibility::{list::List, structurer::repeat};
 
 fuzz_target!(|list: List| {
 	let mut list = list;
@@ -0,0 +1,73 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use perfect_reconstructibility::{
+	directed::strongly_connected_finder::StronglyConnectedFinder, list::List, nodes::Successors,
+	set::Set,
+};
+
+// Finds the nodes of the set reached from the start
+fn find_reached(list: &List, set: &Set, start: usize) -> Vec<bool> {
+	let mut reached = vec![false; list.len()];
+	let mut stack = vec![start];
+
+	while let Some(id) = stack.pop() {
+		if !set[id] || std::mem::replace(&mut reached[id], true) {
+			continue;
+		}
+
+		stack.extend(list.successors(id));
+	}
+
+	reached
+}
+
+// Two nodes share a component if they reach each other, and only components with
+// more than one node are reported
+fn find_components(list: &List, set: &Set) -> Vec<Vec<usize>> {
+	let reached: Vec<_> = (0..list.len())
+		.map(|id| find_reached(list, set, id))
+		.collect();
+
+	let mut components = Vec::new();
+	let mut seen = vec![false; list.len()];
+
+	for id in set.ones() {
+		if seen[id] {
+			continue;
+		}
+
+		let component: Vec<_> = set
+			.ones()
+			.filter(|&other| reached[id][other] && reached[other][id])
+			.collect();
+
+		for &id in &component {
+			seen[id] = true;
+		}
+
+		if component.len() > 1 {
+			components.push(component);
+		}
+	}
+
+	components.sort_unstable();
+	components
+}
+
+fuzz_target!(|input: (List, Vec<bool>)| {
+	let (list, mask) = input;
+	let set: Set = (0..list.len())
+		.filter(|&id| mask.get(id).copied().unwrap_or(true))
+		.collect();
+
+	let mut components = Vec::new();
+
+	StronglyConnectedFinder::new().run(&list, set.as_slice(), |component| {
+		components.push(component.ones().collect::<Vec<_>>());
+	});
+
+	components.sort_unstable();
+
+	assert_eq!(components, find_components(&list, &set));
+});
@@ -0,0 +1,86 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use perfect_reconstructibility::{
+	directed::dominator_finder::DominatorFinder,
+	list::List,
+	nodes::{Successors, Synthetics},
+	structurer::{branch, repeat},
+	tree::Builder,
+};
+
+// Checks that the graph is acyclic once edges to a dominator are removed
+fn is_reducible(list: &List) -> bool {
+	let set = list.ids();
+	let mut dominator_finder = DominatorFinder::new();
+	let mut degrees = vec![0; list.len()];
+
+	dominator_finder.run(list, set.ones(), 0);
+
+	let is_forward = |from: usize, to: usize| !dominator_finder.dominates(to, from);
+
+	for id in set.ones() {
+		for successor in list.successors(id).filter(|&to| is_forward(id, to)) {
+			degrees[successor] += 1;
+		}
+	}
+
+	let mut stack: Vec<_> = set.ones().filter(|&id| degrees[id] == 0).collect();
+	let mut visited = 0;
+
+	while let Some(id) = stack.pop() {
+		visited += 1;
+
+		for successor in list.successors(id).filter(|&to| is_forward(id, to)) {
+			degrees[successor] -= 1;
+
+			if degrees[successor] == 0 {
+				stack.push(successor);
+			}
+		}
+	}
+
+	visited == list.len()
+}
+
+// Checks that `to` follows `from`, either directly or through synthetic nodes only
+fn is_linked(list: &List, from: usize, to: usize) -> bool {
+	let mut seen = vec![false; list.len()];
+	let mut stack: Vec<_> = list.successors(from).collect();
+
+	while let Some(id) = stack.pop() {
+		if id == to {
+			return true;
+		}
+
+		if !std::mem::replace(&mut seen[id], true) && list.synthetic(id).is_some() {
+			stack.extend(list.successors(id));
+		}
+	}
+
+	false
+}
+
+fuzz_target!(|list: List| {
+	let original = list.clone();
+	let mut list = list;
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+	branch::Bulk::new().run(&mut list, &mut set, 0);
+
+	if let Err(error) = Builder::new().run(&list, list.ids().as_slice(), 0) {
+		panic!("restructured graph is not structured: {error}");
+	}
+
+	assert!(is_reducible(&list), "restructured graph is irreducible");
+
+	for id in 0..original.len() {
+		for successor in original.successors(id) {
+			assert!(
+				is_linked(&list, id, successor),
+				"edge {id} -> {successor} was lost"
+			);
+		}
+	}
+});
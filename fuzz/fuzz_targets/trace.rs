@@ -0,0 +1,43 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use perfect_reconstructibility::{
+	interpreter::Interpreter,
+	list::List,
+	structurer::{branch, repeat},
+};
+
+const LIMIT: usize = 256;
+
+// Runs the graph, taking the successors picked by the choices in turn
+fn run(list: &List, choices: &[u8]) -> Vec<usize> {
+	let mut interpreter = Interpreter::new();
+	let mut decision = 0;
+
+	let result = interpreter.run(list, 0, LIMIT, |_, len| {
+		let choice = choices.get(decision % choices.len().max(1)).copied();
+
+		decision += 1;
+
+		usize::from(choice.unwrap_or(0)) % len
+	});
+
+	if let Err(error) = result {
+		panic!("interpreter failed: {error}");
+	}
+
+	interpreter.trace().to_vec()
+}
+
+fuzz_target!(|input: (List, Vec<u8>)| {
+	let (list, choices) = input;
+	let original = run(&list, &choices);
+
+	let mut list = list;
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+	branch::Bulk::new().run(&mut list, &mut set, 0);
+
+	assert_eq!(original, run(&list, &choices), "traces differ");
+});
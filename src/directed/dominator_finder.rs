@@ -4,13 +4,26 @@
 
 use crate::nodes::{Predecessors, Successors};
 
-use super::reverse_post_searcher::ReversePostSearcher;
+use super::{reverse_post_searcher::ReversePostSearcher, semi_nca_finder::SemiNcaFinder};
+
+/// The algorithm used to find the dominators.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+	/// The iterative algorithm, which is fast on reducible graphs but may take
+	/// many passes on irreducible ones.
+	#[default]
+	Iterative,
+
+	/// The Semi-NCA algorithm, which runs in near linear time on any graph.
+	SemiNca,
+}
 
 #[derive(Default)]
 pub struct DominatorFinder {
 	dominators: Vec<usize>,
 
 	reverse_post_searcher: ReversePostSearcher,
+	semi_nca_finder: SemiNcaFinder,
 }
 
 impl DominatorFinder {
@@ -20,6 +33,7 @@ impl DominatorFinder {
 			dominators: Vec::new(),
 
 			reverse_post_searcher: ReversePostSearcher::new(),
+			semi_nca_finder: SemiNcaFinder::new(),
 		}
 	}
 
@@ -55,7 +69,7 @@ impl DominatorFinder {
 	fn has_any_dominator(&self, index: usize) -> bool {
 		self.dominators
 			.get(index)
-			.map_or(false, |&id| id != usize::MAX)
+			.is_some_and(|&id| id != usize::MAX)
 	}
 
 	fn find_dominator<N: Predecessors>(&self, nodes: &N, id: usize) -> usize {
@@ -88,6 +102,35 @@ impl DominatorFinder {
 		}
 	}
 
+	/// Returns the nodes reached from the start in reverse post order.
+	#[must_use]
+	pub fn ids(&self) -> &[usize] {
+		self.reverse_post_searcher.post_to_id()
+	}
+
+	/// Returns the immediate dominator of the node, if it was reached and is not the start.
+	#[must_use]
+	pub fn dominator(&self, id: usize) -> Option<usize> {
+		let index = self
+			.id_to_post_checked(id)
+			.filter(|&index| index != usize::MAX)?;
+		let dominator = self.dominators[index];
+
+		(index != 0).then(|| self.reverse_post_searcher.post_to_id()[dominator])
+	}
+
+	fn run_semi_nca<N: Predecessors + Successors>(&mut self, nodes: &N, start: usize) {
+		let ids = self.reverse_post_searcher.post_to_id();
+
+		self.semi_nca_finder.run(nodes, ids.iter().copied(), start);
+
+		for (index, &id) in ids.iter().enumerate().skip(1) {
+			let dominator = self.semi_nca_finder.dominator(id).unwrap();
+
+			self.dominators[index] = self.reverse_post_searcher.id_to_post()[dominator];
+		}
+	}
+
 	#[must_use]
 	pub fn dominates(&self, dominator: usize, id: usize) -> bool {
 		let dominator = self.reverse_post_searcher.id_to_post()[dominator];
@@ -97,6 +140,14 @@ impl DominatorFinder {
 	}
 
 	pub fn run<N, I>(&mut self, nodes: &N, set: I, start: usize)
+	where
+		N: Predecessors + Successors,
+		I: IntoIterator<Item = usize>,
+	{
+		self.run_with(nodes, set, start, Algorithm::Iterative);
+	}
+
+	pub fn run_with<N, I>(&mut self, nodes: &N, set: I, start: usize, algorithm: Algorithm)
 	where
 		N: Predecessors + Successors,
 		I: IntoIterator<Item = usize>,
@@ -106,6 +157,10 @@ impl DominatorFinder {
 		self.reverse_post_searcher.finalize();
 
 		self.fill_dominators();
-		self.run_heuristic(nodes);
+
+		match algorithm {
+			Algorithm::Iterative => self.run_heuristic(nodes),
+			Algorithm::SemiNca => self.run_semi_nca(nodes, start),
+		}
 	}
 }
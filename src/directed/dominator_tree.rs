@@ -0,0 +1,165 @@
+use crate::nodes::Predecessors;
+
+use super::dominator_finder::DominatorFinder;
+
+/// An immediate dominator tree indexed by node. Unlike [`DominatorFinder`], it can be
+/// kept up to date as nodes are added or moved instead of being recomputed.
+#[derive(Default)]
+pub struct DominatorTree {
+	dominators: Vec<usize>,
+	positions: Vec<usize>,
+}
+
+impl DominatorTree {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			dominators: Vec::new(),
+			positions: Vec::new(),
+		}
+	}
+
+	fn is_known(&self, id: usize) -> bool {
+		self.dominators
+			.get(id)
+			.is_some_and(|&dominator| dominator != usize::MAX)
+	}
+
+	/// Returns the immediate dominator of the node, if it is known and not the root.
+	#[must_use]
+	pub fn dominator(&self, id: usize) -> Option<usize> {
+		self.dominators
+			.get(id)
+			.copied()
+			.filter(|&dominator| dominator != usize::MAX && dominator != id)
+	}
+
+	/// Sets the immediate dominator of the node. A node dominated by itself is a root.
+	pub fn set_dominator(&mut self, id: usize, dominator: usize) {
+		if self.dominators.len() <= id {
+			self.dominators.resize(id + 1, usize::MAX);
+		}
+
+		self.dominators[id] = dominator;
+	}
+
+	#[must_use]
+	pub fn dominates(&self, dominator: usize, id: usize) -> bool {
+		self.dominates_within(dominator, id, usize::MAX)
+	}
+
+	/// Returns whether the node is dominated, giving up once the root of a subtree
+	/// containing both nodes is reached. This avoids walking all the way up the tree.
+	#[must_use]
+	pub fn dominates_within(&self, dominator: usize, mut id: usize, root: usize) -> bool {
+		loop {
+			if id == dominator {
+				return true;
+			}
+
+			if id == root {
+				return false;
+			}
+
+			match self.dominator(id) {
+				Some(next) => id = next,
+				None => return false,
+			}
+		}
+	}
+
+	fn mark_ancestors(&mut self, id: usize) {
+		let mut ancestor = Some(id);
+		let mut position = 0;
+
+		if self.positions.len() < self.dominators.len() {
+			self.positions.resize(self.dominators.len(), usize::MAX);
+		}
+
+		while let Some(id) = ancestor {
+			self.positions[id] = position;
+
+			ancestor = self.dominator(id);
+			position += 1;
+		}
+	}
+
+	fn unmark_ancestors(&mut self, id: usize) {
+		let mut ancestor = Some(id);
+
+		while let Some(id) = ancestor {
+			self.positions[id] = usize::MAX;
+
+			ancestor = self.dominator(id);
+		}
+	}
+
+	fn find_marked(&self, mut id: usize) -> Option<usize> {
+		loop {
+			if self
+				.positions
+				.get(id)
+				.is_some_and(|&position| position != usize::MAX)
+			{
+				return Some(id);
+			}
+
+			id = self.dominator(id)?;
+		}
+	}
+
+	// All intersections lie on the marked path, so the nearest common dominator is
+	// whichever of them is furthest from where the path starts.
+	fn find_intersection_of<I>(&mut self, mut ids: I) -> Option<usize>
+	where
+		I: Iterator<Item = usize>,
+	{
+		let first = ids.next()?;
+		let mut result = Some(first);
+
+		self.mark_ancestors(first);
+
+		for id in ids {
+			result = result.zip(self.find_marked(id)).map(|(result, marked)| {
+				if self.positions[marked] > self.positions[result] {
+					marked
+				} else {
+					result
+				}
+			});
+		}
+
+		self.unmark_ancestors(first);
+
+		result
+	}
+
+	/// Returns the nearest common dominator of both nodes, if they share a root.
+	pub fn find_intersection(&mut self, id_1: usize, id_2: usize) -> Option<usize> {
+		self.find_intersection_of([id_1, id_2].into_iter())
+	}
+
+	/// Sets the immediate dominator of the node to the nearest common dominator of
+	/// all of its known predecessors. Predecessors must be updated before successors.
+	pub fn insert<N: Predecessors>(&mut self, nodes: &N, id: usize) {
+		let predecessors: Vec<_> = nodes
+			.predecessors(id)
+			.filter(|&predecessor| self.is_known(predecessor))
+			.collect();
+
+		if let Some(dominator) = self.find_intersection_of(predecessors.into_iter()) {
+			self.set_dominator(id, dominator);
+		}
+	}
+
+	/// Replaces the tree with the results of the [`DominatorFinder`].
+	pub fn load(&mut self, dominator_finder: &DominatorFinder) {
+		self.dominators.clear();
+
+		for &id in dominator_finder.ids() {
+			let dominator = dominator_finder.dominator(id).unwrap_or(id);
+
+			self.set_dominator(id, dominator);
+		}
+	}
+}
@@ -1,4 +1,6 @@
 pub mod depth_first_searcher;
 pub mod dominator_finder;
+pub mod dominator_tree;
 pub mod reverse_post_searcher;
+pub mod semi_nca_finder;
 pub mod strongly_connected_finder;
@@ -0,0 +1,167 @@
+// Resources:
+// "Finding Dominators in Practice",
+//     by Loukas Georgiadis, Robert E. Tarjan, and Renato F. Werneck
+
+use crate::nodes::{Predecessors, Successors};
+
+use super::depth_first_searcher::DepthFirstSearcher;
+
+#[derive(Default)]
+pub struct SemiNcaFinder {
+	pre_to_id: Vec<usize>,
+	id_to_pre: Vec<usize>,
+	parents: Vec<usize>,
+	semis: Vec<usize>,
+	labels: Vec<usize>,
+	ancestors: Vec<usize>,
+	dominators: Vec<usize>,
+
+	path: Vec<usize>,
+	depth_first_searcher: DepthFirstSearcher,
+}
+
+impl SemiNcaFinder {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			pre_to_id: Vec::new(),
+			id_to_pre: Vec::new(),
+			parents: Vec::new(),
+			semis: Vec::new(),
+			labels: Vec::new(),
+			ancestors: Vec::new(),
+			dominators: Vec::new(),
+
+			path: Vec::new(),
+			depth_first_searcher: DepthFirstSearcher::new(),
+		}
+	}
+
+	fn find_pre_order<N: Successors>(&mut self, nodes: &N, start: usize) {
+		self.pre_to_id.clear();
+		self.parents.clear();
+
+		self.depth_first_searcher.run(nodes, start, |id, post| {
+			if post {
+				self.path.pop();
+			} else {
+				let parent = self.path.last().copied().unwrap_or(usize::MAX);
+
+				self.path.push(id);
+				self.pre_to_id.push(id);
+				self.parents.push(parent);
+			}
+		});
+
+		let last = self.pre_to_id.iter().max().map_or(0, |id| id + 1);
+
+		self.id_to_pre.clear();
+		self.id_to_pre.resize(last, usize::MAX);
+
+		for (index, &id) in self.pre_to_id.iter().enumerate() {
+			self.id_to_pre[id] = index;
+		}
+
+		for parent in &mut self.parents[1..] {
+			*parent = self.id_to_pre[*parent];
+		}
+	}
+
+	fn id_to_pre_checked(&self, id: usize) -> Option<usize> {
+		self.id_to_pre
+			.get(id)
+			.copied()
+			.filter(|&index| index != usize::MAX)
+	}
+
+	fn compress(&mut self, index: usize) {
+		let mut current = index;
+
+		while self.ancestors[self.ancestors[current]] != usize::MAX {
+			self.path.push(current);
+
+			current = self.ancestors[current];
+		}
+
+		while let Some(current) = self.path.pop() {
+			let ancestor = self.ancestors[current];
+
+			if self.semis[self.labels[ancestor]] < self.semis[self.labels[current]] {
+				self.labels[current] = self.labels[ancestor];
+			}
+
+			self.ancestors[current] = self.ancestors[ancestor];
+		}
+	}
+
+	fn evaluate(&mut self, index: usize) -> usize {
+		if self.ancestors[index] == usize::MAX {
+			index
+		} else {
+			self.compress(index);
+
+			self.labels[index]
+		}
+	}
+
+	fn find_semi_dominators<N: Predecessors>(&mut self, nodes: &N) {
+		let len = self.pre_to_id.len();
+
+		self.semis.clear();
+		self.semis.extend(0..len);
+		self.labels.clear();
+		self.labels.extend(0..len);
+		self.ancestors.clear();
+		self.ancestors.resize(len, usize::MAX);
+
+		for index in (1..len).rev() {
+			let id = self.pre_to_id[index];
+			let predecessors: Vec<_> = nodes
+				.predecessors(id)
+				.filter_map(|predecessor| self.id_to_pre_checked(predecessor))
+				.collect();
+
+			for predecessor in predecessors {
+				let label = self.evaluate(predecessor);
+
+				self.semis[index] = self.semis[index].min(self.semis[label]);
+			}
+
+			self.ancestors[index] = self.parents[index];
+		}
+	}
+
+	fn find_dominators(&mut self) {
+		self.dominators.clone_from(&self.parents);
+
+		for index in 1..self.dominators.len() {
+			let mut dominator = self.dominators[index];
+
+			while dominator > self.semis[index] {
+				dominator = self.dominators[dominator];
+			}
+
+			self.dominators[index] = dominator;
+		}
+	}
+
+	/// Returns the immediate dominator of the node, if it was reached and is not the start.
+	#[must_use]
+	pub fn dominator(&self, id: usize) -> Option<usize> {
+		let index = self.id_to_pre_checked(id).filter(|&index| index != 0)?;
+
+		Some(self.pre_to_id[self.dominators[index]])
+	}
+
+	pub fn run<N, I>(&mut self, nodes: &N, set: I, start: usize)
+	where
+		N: Predecessors + Successors,
+		I: IntoIterator<Item = usize>,
+	{
+		self.depth_first_searcher.restrict(set);
+
+		self.find_pre_order(nodes, start);
+		self.find_semi_dominators(nodes);
+		self.find_dominators();
+	}
+}
@@ -35,8 +35,10 @@ impl StronglyConnectedFinder {
 			.max()
 			.map_or(0, |index| index + 1);
 
-		self.names.clear();
-		self.names.resize(last, usize::MAX);
+		// Names are reset as components are popped, so they only need to grow
+		if self.names.len() < last {
+			self.names.resize(last, usize::MAX);
+		}
 	}
 
 	fn on_pre_order<N: Successors>(&mut self, nodes: &N, id: usize) {
@@ -0,0 +1,5 @@
+mod reader;
+mod writer;
+
+pub use reader::{Error, ErrorKind, Reader};
+pub use writer::{Region, RegionKind, Writer};
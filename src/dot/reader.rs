@@ -0,0 +1,652 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	list::{Instruction, List},
+	nodes::{EdgeKind, Var},
+};
+
+/// The ways reading a graph can fail.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ErrorKind {
+	UnexpectedEnd,
+	UnexpectedToken(String),
+	UnterminatedString,
+	UndirectedEdge,
+	UnknownKind(String),
+	UnknownEdgeKind(String),
+	UnknownVar(String),
+	InvalidValue(String),
+	MissingVar,
+	MissingValue,
+}
+
+/// An error found while reading a graph, with the line it was found on.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Error {
+	pub line: usize,
+	pub kind: ErrorKind,
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "line {}: ", self.line)?;
+
+		match &self.kind {
+			ErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+			ErrorKind::UnexpectedToken(token) => write!(f, "unexpected `{token}`"),
+			ErrorKind::UnterminatedString => write!(f, "unterminated string"),
+			ErrorKind::UndirectedEdge => write!(f, "undirected edges are not supported"),
+			ErrorKind::UnknownKind(kind) => write!(f, "unknown node kind `{kind}`"),
+			ErrorKind::UnknownEdgeKind(kind) => write!(f, "unknown edge kind `{kind}`"),
+			ErrorKind::UnknownVar(var) => write!(f, "unknown variable `{var}`"),
+			ErrorKind::InvalidValue(value) => write!(f, "invalid value `{value}`"),
+			ErrorKind::MissingVar => write!(f, "synthetic node without a `var`"),
+			ErrorKind::MissingValue => write!(f, "assignment without a `value`"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+	Id(String),
+	Arrow,
+	Line,
+	Open,
+	Close,
+	OpenList,
+	CloseList,
+	Equal,
+	Separator,
+	Colon,
+}
+
+impl std::fmt::Display for Token {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Id(id) => write!(f, "{id}"),
+			Self::Arrow => write!(f, "->"),
+			Self::Line => write!(f, "--"),
+			Self::Open => write!(f, "{{"),
+			Self::Close => write!(f, "}}"),
+			Self::OpenList => write!(f, "["),
+			Self::CloseList => write!(f, "]"),
+			Self::Equal => write!(f, "="),
+			Self::Separator => write!(f, ";"),
+			Self::Colon => write!(f, ":"),
+		}
+	}
+}
+
+// What the attributes of a statement are given to
+#[derive(Clone, Copy)]
+enum Target {
+	None,
+	Node(usize),
+	Edges(usize),
+}
+
+#[derive(Default)]
+struct Attributes {
+	kind: Option<(String, usize)>,
+	var: Option<(String, usize)>,
+	value: Option<(String, usize)>,
+}
+
+/// This structure reads a subset of the DOT language into a [`List`]. Successors are
+/// kept in the order their edges appear.
+///
+/// Nodes named by a number, optionally after `node_` as the [`Writer`](super::Writer)
+/// names them, keep that number as their id if the numbers are exactly `0` up to the
+/// number of nodes. Otherwise, nodes are numbered in the order they first appear, so
+/// the entry should be mentioned first.
+///
+/// Nodes may have a `kind` attribute of `simple`, `selection`, `assign`, or `nop`,
+/// along with `var` set to `destination`, `repetition`, `branch`, or `exit` and a
+/// numeric `value`.
+/// Edges may have a `kind` attribute of `normal`, `pinned`, or `ignored`. Subgraphs are
+/// flattened and all other attributes are ignored.
+#[derive(Default)]
+pub struct Reader {
+	tokens: Vec<(Token, usize)>,
+	position: usize,
+
+	names: BTreeMap<String, usize>,
+	attributes: Vec<Attributes>,
+	links: Vec<(usize, usize, EdgeKind)>,
+}
+
+impl Reader {
+	/// Creates a new instance of the reader.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			tokens: Vec::new(),
+			position: 0,
+
+			names: BTreeMap::new(),
+			attributes: Vec::new(),
+			links: Vec::new(),
+		}
+	}
+
+	fn read_quoted(
+		chars: &mut std::iter::Peekable<std::str::Chars>,
+		line: &mut usize,
+	) -> Option<String> {
+		let mut result = String::new();
+
+		loop {
+			match chars.next()? {
+				'"' => return Some(result),
+				'\\' => match chars.next()? {
+					'"' => result.push('"'),
+					'\n' => *line += 1,
+					other => {
+						result.push('\\');
+						result.push(other);
+					}
+				},
+				other => {
+					if other == '\n' {
+						*line += 1;
+					}
+
+					result.push(other);
+				}
+			}
+		}
+	}
+
+	fn read_html(
+		chars: &mut std::iter::Peekable<std::str::Chars>,
+		line: &mut usize,
+	) -> Option<String> {
+		let mut result = String::new();
+		let mut depth = 1;
+
+		loop {
+			let other = chars.next()?;
+
+			match other {
+				'<' => depth += 1,
+				'>' => depth -= 1,
+				'\n' => *line += 1,
+				_ => {}
+			}
+
+			if depth == 0 {
+				return Some(result);
+			}
+
+			result.push(other);
+		}
+	}
+
+	fn skip_comment(chars: &mut std::iter::Peekable<std::str::Chars>, line: &mut usize) {
+		let mut last = '\0';
+
+		for other in chars.by_ref() {
+			if other == '\n' {
+				*line += 1;
+			}
+
+			if last == '*' && other == '/' {
+				return;
+			}
+
+			last = other;
+		}
+	}
+
+	fn tokenize(&mut self, source: &str) -> Result<(), Error> {
+		let mut chars = source.chars().peekable();
+		let mut line = 1;
+		let mut start_of_line = true;
+
+		self.tokens.clear();
+		self.position = 0;
+
+		while let Some(current) = chars.next() {
+			let token = match current {
+				'\n' => {
+					line += 1;
+					start_of_line = true;
+
+					continue;
+				}
+				'#' if start_of_line => {
+					chars
+						.by_ref()
+						.take_while(|&other| other != '\n')
+						.for_each(drop);
+
+					line += 1;
+
+					continue;
+				}
+				'/' if chars.peek() == Some(&'/') => {
+					chars
+						.by_ref()
+						.take_while(|&other| other != '\n')
+						.for_each(drop);
+
+					line += 1;
+					start_of_line = true;
+
+					continue;
+				}
+				'/' if chars.peek() == Some(&'*') => {
+					chars.next();
+
+					Self::skip_comment(&mut chars, &mut line);
+
+					continue;
+				}
+				other if other.is_whitespace() => continue,
+				'-' if chars.peek() == Some(&'>') => {
+					chars.next();
+
+					Token::Arrow
+				}
+				'-' if chars.peek() == Some(&'-') => {
+					chars.next();
+
+					Token::Line
+				}
+				'{' => Token::Open,
+				'}' => Token::Close,
+				'[' => Token::OpenList,
+				']' => Token::CloseList,
+				'=' => Token::Equal,
+				';' | ',' => Token::Separator,
+				':' => Token::Colon,
+				'"' => {
+					let start = line;
+					let text = Self::read_quoted(&mut chars, &mut line).ok_or(Error {
+						line: start,
+						kind: ErrorKind::UnterminatedString,
+					})?;
+
+					Token::Id(text)
+				}
+				'<' => {
+					let start = line;
+					let text = Self::read_html(&mut chars, &mut line).ok_or(Error {
+						line: start,
+						kind: ErrorKind::UnterminatedString,
+					})?;
+
+					Token::Id(text)
+				}
+				other if other.is_alphanumeric() || matches!(other, '_' | '.' | '-') => {
+					let mut text = String::from(other);
+
+					while let Some(&next) = chars.peek() {
+						if !next.is_alphanumeric() && !matches!(next, '_' | '.') {
+							break;
+						}
+
+						text.push(next);
+						chars.next();
+					}
+
+					Token::Id(text)
+				}
+				other => {
+					return Err(Error {
+						line,
+						kind: ErrorKind::UnexpectedToken(other.to_string()),
+					})
+				}
+			};
+
+			start_of_line = false;
+
+			self.tokens.push((token, line));
+		}
+
+		Ok(())
+	}
+
+	fn line(&self) -> usize {
+		self.tokens
+			.get(self.position)
+			.or_else(|| self.tokens.last())
+			.map_or(1, |&(_, line)| line)
+	}
+
+	fn error(&self, kind: ErrorKind) -> Error {
+		Error {
+			line: self.line(),
+			kind,
+		}
+	}
+
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.position).map(|(token, _)| token)
+	}
+
+	fn next(&mut self) -> Result<Token, Error> {
+		let token = self
+			.peek()
+			.cloned()
+			.ok_or_else(|| self.error(ErrorKind::UnexpectedEnd))?;
+
+		self.position += 1;
+
+		Ok(token)
+	}
+
+	fn expect(&mut self, expected: &Token) -> Result<(), Error> {
+		let line = self.line();
+		let token = self.next()?;
+
+		if &token == expected {
+			Ok(())
+		} else {
+			Err(Error {
+				line,
+				kind: ErrorKind::UnexpectedToken(token.to_string()),
+			})
+		}
+	}
+
+	fn next_id(&mut self) -> Result<String, Error> {
+		let line = self.line();
+
+		match self.next()? {
+			Token::Id(id) => Ok(id),
+			token => Err(Error {
+				line,
+				kind: ErrorKind::UnexpectedToken(token.to_string()),
+			}),
+		}
+	}
+
+	fn is_keyword(&self, keyword: &str) -> bool {
+		matches!(self.peek(), Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword))
+	}
+
+	fn skip_separator(&mut self) {
+		if self.peek() == Some(&Token::Separator) {
+			self.position += 1;
+		}
+	}
+
+	fn find_node(&mut self, name: String) -> usize {
+		let len = self.names.len();
+		let id = *self.names.entry(name).or_insert(len);
+
+		if id == self.attributes.len() {
+			self.attributes.push(Attributes::default());
+		}
+
+		id
+	}
+
+	fn read_node_id(&mut self) -> Result<usize, Error> {
+		let name = self.next_id()?;
+
+		// Ports are not needed to know which node is meant
+		while self.peek() == Some(&Token::Colon) {
+			self.position += 1;
+
+			self.next_id()?;
+		}
+
+		Ok(self.find_node(name))
+	}
+
+	fn parse_edge_kind(kind: &str, line: usize) -> Result<EdgeKind, Error> {
+		match kind.to_ascii_lowercase().as_str() {
+			"normal" => Ok(EdgeKind::Normal),
+			"pinned" => Ok(EdgeKind::Pinned),
+			"ignored" => Ok(EdgeKind::Ignored),
+			_ => Err(Error {
+				line,
+				kind: ErrorKind::UnknownEdgeKind(kind.to_string()),
+			}),
+		}
+	}
+
+	fn read_attributes(&mut self, target: Target) -> Result<(), Error> {
+		while self.peek() == Some(&Token::OpenList) {
+			self.position += 1;
+
+			while self.peek() != Some(&Token::CloseList) {
+				let line = self.line();
+				let key = self.next_id()?;
+
+				self.expect(&Token::Equal)?;
+
+				let value = self.next_id()?;
+
+				match target {
+					Target::Node(id) => {
+						let attributes = &mut self.attributes[id];
+						let slot = match key.as_str() {
+							"kind" => &mut attributes.kind,
+							"var" => &mut attributes.var,
+							"value" => &mut attributes.value,
+							_ => &mut None,
+						};
+
+						*slot = Some((value, line));
+					}
+					Target::Edges(start) if key == "kind" => {
+						let kind = Self::parse_edge_kind(&value, line)?;
+
+						for link in &mut self.links[start..] {
+							link.2 = kind;
+						}
+					}
+					Target::Edges(_) | Target::None => {}
+				}
+
+				self.skip_separator();
+			}
+
+			self.position += 1;
+		}
+
+		Ok(())
+	}
+
+	fn read_statement(&mut self) -> Result<(), Error> {
+		if self.is_keyword("graph") || self.is_keyword("node") || self.is_keyword("edge") {
+			self.position += 1;
+
+			return self.read_attributes(Target::None);
+		}
+
+		if self.is_keyword("subgraph") {
+			self.position += 1;
+
+			if self.peek() != Some(&Token::Open) {
+				self.next_id()?;
+			}
+		}
+
+		if self.peek() == Some(&Token::Open) {
+			self.position += 1;
+
+			return self.read_statements();
+		}
+
+		if self.tokens.get(self.position + 1).map(|(token, _)| token) == Some(&Token::Equal) {
+			self.position += 3;
+
+			return Ok(());
+		}
+
+		let mut from = self.read_node_id()?;
+		let start = self.links.len();
+
+		loop {
+			match self.peek() {
+				Some(Token::Arrow) => {
+					self.position += 1;
+
+					let to = self.read_node_id()?;
+
+					self.links.push((from, to, EdgeKind::Normal));
+
+					from = to;
+				}
+				Some(Token::Line) => return Err(self.error(ErrorKind::UndirectedEdge)),
+				_ => break,
+			}
+		}
+
+		// Attributes after a chain of edges apply to all of them
+		if self.links.len() == start {
+			self.read_attributes(Target::Node(from))
+		} else {
+			self.read_attributes(Target::Edges(start))
+		}
+	}
+
+	fn read_statements(&mut self) -> Result<(), Error> {
+		while self.peek() != Some(&Token::Close) {
+			self.read_statement()?;
+			self.skip_separator();
+		}
+
+		self.position += 1;
+
+		Ok(())
+	}
+
+	fn read_graph(&mut self) -> Result<(), Error> {
+		if self.is_keyword("strict") {
+			self.position += 1;
+		}
+
+		if !self.is_keyword("digraph") {
+			let token = self.next()?;
+
+			return Err(self.error(ErrorKind::UnexpectedToken(token.to_string())));
+		}
+
+		self.position += 1;
+
+		if self.peek() != Some(&Token::Open) {
+			self.next_id()?;
+		}
+
+		self.expect(&Token::Open)?;
+		self.read_statements()
+	}
+
+	fn parse_var((var, line): &(String, usize)) -> Result<Var, Error> {
+		match var.to_ascii_lowercase().as_str() {
+			"destination" => Ok(Var::Destination),
+			"repetition" => Ok(Var::Repetition),
+			"branch" => Ok(Var::Branch),
+			"exit" => Ok(Var::Exit),
+			_ => Err(Error {
+				line: *line,
+				kind: ErrorKind::UnknownVar(var.clone()),
+			}),
+		}
+	}
+
+	fn parse_value((value, line): &(String, usize)) -> Result<usize, Error> {
+		value.parse().map_err(|_| Error {
+			line: *line,
+			kind: ErrorKind::InvalidValue(value.clone()),
+		})
+	}
+
+	fn find_instruction(attributes: &Attributes, line: usize) -> Result<Instruction, Error> {
+		let Some((kind, kind_line)) = &attributes.kind else {
+			return Ok(Instruction::Simple);
+		};
+
+		let var = attributes.var.as_ref().ok_or(Error {
+			line: *kind_line,
+			kind: ErrorKind::MissingVar,
+		});
+
+		match kind.to_ascii_lowercase().as_str() {
+			"simple" => Ok(Instruction::Simple),
+			"nop" => Ok(Instruction::NoOperation),
+			"selection" => Ok(Instruction::Selection {
+				var: Self::parse_var(var?)?,
+			}),
+			"assign" => {
+				let value = attributes.value.as_ref().ok_or(Error {
+					line: *kind_line,
+					kind: ErrorKind::MissingValue,
+				})?;
+
+				Ok(Instruction::SetVariable {
+					var: Self::parse_var(var?)?,
+					value: Self::parse_value(value)?,
+				})
+			}
+			_ => Err(Error {
+				line,
+				kind: ErrorKind::UnknownKind(kind.clone()),
+			}),
+		}
+	}
+
+	// Nodes keep the ids in their names only if every id is named once
+	fn find_named_ids(&self) -> Option<Vec<usize>> {
+		let mut ids = vec![usize::MAX; self.names.len()];
+		let mut is_used = vec![false; self.names.len()];
+
+		for (name, &index) in &self.names {
+			let id: usize = name.strip_prefix("node_").unwrap_or(name).parse().ok()?;
+
+			if std::mem::replace(is_used.get_mut(id)?, true) {
+				return None;
+			}
+
+			ids[index] = id;
+		}
+
+		Some(ids)
+	}
+
+	/// Reads the `digraph` in the source into a new [`List`].
+	///
+	/// # Errors
+	///
+	/// Returns an error if the source is not a supported `digraph`, a node has invalid
+	/// `kind`, `var`, or `value` attributes, or an edge has an invalid `kind`.
+	pub fn run(&mut self, source: &str) -> Result<List, Error> {
+		self.names.clear();
+		self.attributes.clear();
+		self.links.clear();
+
+		self.tokenize(source)?;
+		self.read_graph()?;
+
+		let ids = self
+			.find_named_ids()
+			.unwrap_or_else(|| (0..self.names.len()).collect());
+		let mut instructions = vec![Instruction::Simple; ids.len()];
+
+		for (attributes, &id) in self.attributes.iter().zip(&ids) {
+			let line = attributes.kind.as_ref().map_or(1, |&(_, line)| line);
+
+			instructions[id] = Self::find_instruction(attributes, line)?;
+		}
+
+		let mut list = List::with_capacity(ids.len());
+
+		for instruction in instructions {
+			list.add_instruction(instruction);
+		}
+
+		for &(from, to, kind) in &self.links {
+			list.add_link_with_kind(ids[from], ids[to], kind);
+		}
+
+		Ok(list)
+	}
+}
@@ -0,0 +1,291 @@
+use std::io::{Result, Write};
+
+use crate::{
+	nodes::{EdgeKind, Nodes, Successors, Synthetic, Synthetics, Var},
+	set::{Set, Slice},
+	tree::selected_value,
+};
+
+const SIMPLE_COLOR: &str = "#DDDDFF";
+const NO_OPERATION_COLOR: &str = "#E8E8E8";
+const LOOP_COLOR: &str = "#EEF6FF";
+const BRANCH_COLOR: &str = "#FFF6E6";
+
+/// The kind of construct a [`Region`] was restructured into.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum RegionKind {
+	Loop,
+	Branch,
+}
+
+/// A set of nodes drawn as a cluster, such as a loop or a branch with its head.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Region {
+	pub kind: RegionKind,
+	pub start: usize,
+	pub set: Set,
+}
+
+// Lets graphs that cannot tell synthetic nodes apart be written like any other
+struct Original<'a, N>(&'a N);
+
+impl<N: Successors> Successors for Original<'_, N> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.0.successors(id)
+	}
+}
+
+impl<N> Synthetics for Original<'_, N> {
+	fn synthetic(&self, _: usize) -> Option<Synthetic> {
+		None
+	}
+}
+
+const fn var_name(var: Var) -> &'static str {
+	match var {
+		Var::Destination => "destination",
+		Var::Repetition => "repetition",
+		Var::Branch => "branch",
+		Var::Exit => "exit",
+	}
+}
+
+const fn var_color(var: Var) -> &'static str {
+	match var {
+		Var::Destination => "#FFDDDD",
+		Var::Repetition => "#DDFFDD",
+		Var::Branch => "#FFEEBB",
+		Var::Exit => "#EEDDFF",
+	}
+}
+
+/// This structure writes a graph in the DOT language. Regions are drawn as nested
+/// clusters, edges leaving nodes with several successors are labelled with the value
+/// that selects them, and synthetic nodes are colored by the variable they use.
+///
+/// Synthetic nodes and links that are not [`EdgeKind::Normal`] keep the attributes
+/// understood by the [`Reader`](super::Reader), and nodes are named by their id, so a
+/// graph written whole reads back the same.
+#[derive(Default)]
+pub struct Writer {
+	regions: Vec<Region>,
+	order: Vec<usize>,
+	children: Vec<Vec<usize>>,
+	members: Vec<Vec<usize>>,
+	owners: Vec<usize>,
+}
+
+impl Writer {
+	/// Creates a new instance of the writer.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			regions: Vec::new(),
+			order: Vec::new(),
+			children: Vec::new(),
+			members: Vec::new(),
+			owners: Vec::new(),
+		}
+	}
+
+	/// Returns the regions drawn as clusters. They must either nest or not overlap.
+	pub fn regions_mut(&mut self) -> &mut Vec<Region> {
+		&mut self.regions
+	}
+
+	// Regions are visited from largest to smallest, so the innermost region seen so
+	// far that holds the start is the parent.
+	fn find_nesting(&mut self, set: Slice) {
+		let last = set.ones().max().map_or(0, |id| id + 1);
+
+		self.order.clear();
+		self.order.extend(0..self.regions.len());
+		self.order
+			.sort_by_key(|&index| std::cmp::Reverse(self.regions[index].set.ones().count()));
+
+		self.children.clear();
+		self.children.resize_with(self.regions.len() + 1, Vec::new);
+
+		self.owners.clear();
+		self.owners.resize(last, self.regions.len());
+
+		for &index in &self.order {
+			let Region {
+				start, set: region, ..
+			} = &self.regions[index];
+			let parent = self
+				.owners
+				.get(*start)
+				.copied()
+				.unwrap_or(self.regions.len());
+
+			self.children[parent].push(index);
+
+			for id in region.ones().filter(|&id| set[id]) {
+				self.owners[id] = index;
+			}
+		}
+
+		self.members.clear();
+		self.members.resize_with(self.regions.len(), Vec::new);
+
+		for id in set.ones() {
+			if let Some(members) = self.members.get_mut(self.owners[id]) {
+				members.push(id);
+			}
+		}
+	}
+
+	fn write_node<N: Synthetics, W: Write>(nodes: &N, id: usize, out: &mut W) -> Result<()> {
+		write!(out, "\tnode_{id} [label=\"NODE {id}\\l")?;
+
+		match nodes.synthetic(id) {
+			Some(Synthetic::NoOperation) => {
+				write!(
+					out,
+					"No Operation\", kind=nop, fillcolor=\"{NO_OPERATION_COLOR}\""
+				)?;
+			}
+			Some(Synthetic::Selection(var)) => {
+				let name = var_name(var);
+				let color = var_color(var);
+
+				write!(
+					out,
+					"Selection {var:?}\", kind=selection, var={name}, fillcolor=\"{color}\""
+				)?;
+			}
+			Some(Synthetic::Assignment(var, value)) => {
+				let name = var_name(var);
+				let color = var_color(var);
+
+				write!(
+					out,
+					"{var:?} := {value}\", kind=assign, var={name}, value={value}, fillcolor=\"{color}\""
+				)?;
+			}
+			None => write!(out, "\"")?,
+		}
+
+		writeln!(out, "];")
+	}
+
+	fn write_region<W: Write>(&self, index: usize, depth: usize, out: &mut W) -> Result<()> {
+		let indent = "\t".repeat(depth);
+		let Region { kind, start, .. } = &self.regions[index];
+		let (name, color) = match kind {
+			RegionKind::Loop => ("Loop", LOOP_COLOR),
+			RegionKind::Branch => ("Branch", BRANCH_COLOR),
+		};
+
+		writeln!(out, "{indent}subgraph cluster_{index} {{")?;
+		writeln!(out, "{indent}\tlabel = \"{name} {start}\";")?;
+		writeln!(out, "{indent}\tfillcolor = \"{color}\";")?;
+
+		for id in &self.members[index] {
+			writeln!(out, "{indent}\tnode_{id};")?;
+		}
+
+		for &child in &self.children[index] {
+			self.write_region(child, depth + 1, out)?;
+		}
+
+		writeln!(out, "{indent}}}")
+	}
+
+	fn write_links<N, K, W>(nodes: &N, edge_kind: K, set: Slice, out: &mut W) -> Result<()>
+	where
+		N: Successors + Synthetics,
+		K: Fn(usize, usize) -> EdgeKind,
+		W: Write,
+	{
+		for id in set.ones() {
+			let is_labelled = nodes.successors(id).nth(1).is_some();
+
+			for (index, successor) in nodes.successors(id).enumerate() {
+				if !set[successor] {
+					continue;
+				}
+
+				let mut attributes = Vec::new();
+
+				if is_labelled {
+					let value = selected_value(nodes, id, index);
+
+					attributes.push(format!("label=\"{value}\""));
+				}
+
+				match edge_kind(id, successor) {
+					EdgeKind::Normal => {}
+					EdgeKind::Pinned => attributes.push("kind=pinned, style=bold".into()),
+					EdgeKind::Ignored => attributes.push("kind=ignored, style=dashed".into()),
+				}
+
+				if attributes.is_empty() {
+					writeln!(out, "\tnode_{id} -> node_{successor};")?;
+				} else {
+					let attributes = attributes.join(", ");
+
+					writeln!(out, "\tnode_{id} -> node_{successor} [{attributes}];")?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn write<N, K, W>(&mut self, nodes: &N, edge_kind: K, set: Slice, out: &mut W) -> Result<()>
+	where
+		N: Successors + Synthetics,
+		K: Fn(usize, usize) -> EdgeKind,
+		W: Write,
+	{
+		self.find_nesting(set);
+
+		writeln!(out, "digraph {{")?;
+		writeln!(out, "\tstyle = filled;")?;
+		writeln!(
+			out,
+			"\tnode [shape = plain, style = filled, fillcolor = \"{SIMPLE_COLOR}\"];"
+		)?;
+
+		for id in set.ones() {
+			Self::write_node(nodes, id, out)?;
+		}
+
+		for &index in &self.children[self.regions.len()] {
+			self.write_region(index, 1, out)?;
+		}
+
+		Self::write_links(nodes, edge_kind, set, out)?;
+
+		writeln!(out, "}}")
+	}
+
+	/// Writes the nodes in the set, the links between them, and any regions.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run<N, W>(&mut self, nodes: &N, set: Slice, out: &mut W) -> Result<()>
+	where
+		N: Nodes + Synthetics,
+		W: Write,
+	{
+		self.write(nodes, |from, to| nodes.edge_kind(from, to), set, out)
+	}
+
+	/// Writes the nodes like [`Writer::run`], for graphs that cannot tell synthetic
+	/// nodes apart. Every node is drawn as an original node and every link as normal.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run_original<N, W>(&mut self, nodes: &N, set: Slice, out: &mut W) -> Result<()>
+	where
+		N: Successors,
+		W: Write,
+	{
+		self.write(&Original(nodes), |_, _| EdgeKind::Normal, set, out)
+	}
+}
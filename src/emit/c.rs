@@ -0,0 +1,543 @@
+use std::fmt::{Result, Write};
+
+use crate::{
+	nodes::{Predecessors, Successors, Synthetic, Synthetics, Var},
+	set::{Set, Slice},
+	tree::{selected_value, Case, Condition, Selector, Statement},
+};
+
+use super::{find_selector, find_vars, var_name, write_indent, write_lines, Blocks};
+
+/// A part of a graph left unstructured, to be written with labels and `goto` by
+/// [`Emitter::run_mixed`]. It is entered only at its entry and leaves to at most one
+/// node outside of it.
+#[derive(Clone, Debug)]
+pub struct Unstructured {
+	set: Set,
+	entry: usize,
+	exit: Option<usize>,
+}
+
+impl Unstructured {
+	/// Creates a region from the nodes in the set, or returns `None` if the entry is not
+	/// in the set, another node is entered from outside of it, or it leaves to more
+	/// than one node.
+	pub fn new<N>(nodes: &N, set: Set, entry: usize) -> Option<Self>
+	where
+		N: Predecessors + Successors,
+	{
+		let mut exit = None;
+
+		if !set[entry] {
+			return None;
+		}
+
+		for id in set.ones() {
+			if id != entry && nodes.predecessors(id).any(|predecessor| !set[predecessor]) {
+				return None;
+			}
+
+			for successor in nodes.successors(id).filter(|&successor| !set[successor]) {
+				if *exit.get_or_insert(successor) != successor {
+					return None;
+				}
+			}
+		}
+
+		Some(Self { set, entry, exit })
+	}
+
+	/// Returns the nodes of the region.
+	#[must_use]
+	pub fn set(&self) -> Slice<'_> {
+		self.set.as_slice()
+	}
+
+	/// Returns the node the region is entered at.
+	#[must_use]
+	pub const fn entry(&self) -> usize {
+		self.entry
+	}
+
+	/// Returns the node outside the region it leaves to, if any.
+	#[must_use]
+	pub const fn exit(&self) -> Option<usize> {
+		self.exit
+	}
+}
+
+/// A graph where each unstructured region stands in for its entry, as a single node
+/// leading to the node the region leaves to. The other nodes of the region have no
+/// links. Building the tree of this graph with
+/// [`crate::tree::Builder`] gives the statements for [`Emitter::run_mixed`].
+pub struct Collapsed<'a, N> {
+	nodes: &'a N,
+	regions: &'a [Unstructured],
+}
+
+impl<'a, N> Collapsed<'a, N> {
+	/// Creates a view of the graph with the regions collapsed.
+	#[must_use]
+	pub const fn new(nodes: &'a N, regions: &'a [Unstructured]) -> Self {
+		Self { nodes, regions }
+	}
+
+	fn find_entered(&self, id: usize) -> Option<&'a Unstructured> {
+		self.regions.iter().find(|region| region.entry == id)
+	}
+
+	fn find_containing(&self, id: usize) -> Option<&'a Unstructured> {
+		self.regions.iter().find(|region| region.set[id])
+	}
+
+	// Nodes of a region other than its entry have no links in the view
+	fn is_inner(&self, id: usize) -> bool {
+		self.find_containing(id)
+			.is_some_and(|region| region.entry != id)
+	}
+}
+
+impl<N: Predecessors> Predecessors for Collapsed<'_, N> {
+	fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		let is_inner = self.is_inner(id);
+		let entries = self
+			.regions
+			.iter()
+			.filter(move |region| region.exit == Some(id))
+			.map(|region| region.entry);
+
+		self.nodes
+			.predecessors(id)
+			.filter(move |&predecessor| self.find_containing(predecessor).is_none())
+			.chain(entries)
+			.filter(move |_| !is_inner)
+	}
+}
+
+impl<N: Successors> Successors for Collapsed<'_, N> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		let region = self.find_containing(id);
+		let exit = region.and_then(|region| region.exit);
+		let exit = exit.filter(|_| region.is_some_and(|region| region.entry == id));
+
+		exit.into_iter()
+			.chain(self.nodes.successors(id).filter(move |_| region.is_none()))
+	}
+}
+
+impl<N: Synthetics> Synthetics for Collapsed<'_, N> {
+	fn synthetic(&self, id: usize) -> Option<Synthetic> {
+		if self.find_entered(id).is_some() {
+			return None;
+		}
+
+		self.nodes.synthetic(id)
+	}
+}
+
+// The blocks of a mixed run, where the entry of each region holds all of its nodes
+struct Mixed<'a, N, B> {
+	nodes: &'a N,
+	regions: &'a [Unstructured],
+	blocks: &'a mut B,
+}
+
+impl<N, B> Blocks for Mixed<'_, N, B>
+where
+	N: Successors + Synthetics,
+	B: Blocks,
+{
+	fn contents(&mut self, id: usize) -> String {
+		let Some(region) = self.regions.iter().find(|region| region.entry == id) else {
+			return self.blocks.contents(id);
+		};
+
+		let mut contents = String::new();
+
+		Emitter::new()
+			.write_region(self.nodes, region, self.blocks, &mut contents)
+			.expect("writing to a `String` should not fail");
+
+		contents
+	}
+
+	fn selector(&mut self, id: usize) -> String {
+		self.blocks.selector(id)
+	}
+}
+
+/// This structure prints a tree as the body of a C function. Loops become
+/// `do { ... } while`, selections with one or two arms become `if`, and the rest
+/// become `switch`. The synthetic variables used are declared as `int` locals up front.
+///
+/// Regions left unstructured can be written with [`Emitter::run_unstructured`]
+/// instead, which gives each node a label and moves between them with `goto`, or
+/// mixed into the structured output with [`Emitter::run_mixed`].
+#[derive(Default)]
+pub struct Emitter {
+	depth: usize,
+}
+
+impl Emitter {
+	/// Creates a new instance of the emitter.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { depth: 0 }
+	}
+
+	fn find_condition(selector: Selector, name: &str, values: &[usize]) -> String {
+		if selector == Selector::Var(Var::Repetition) && values == [1] {
+			return name.to_string();
+		}
+
+		let comparisons: Vec<_> = values
+			.iter()
+			.map(|value| format!("{name} == {value}"))
+			.collect();
+
+		comparisons.join(" || ")
+	}
+
+	fn write_line<W: Write>(&self, out: &mut W, line: &str) -> Result {
+		write_indent(out, self.depth)?;
+		writeln!(out, "{line}")
+	}
+
+	fn find_synthetic_vars<N: Synthetics>(nodes: &N, set: Slice, vars: &mut Vec<Var>) {
+		vars.extend(set.ones().filter_map(|id| match nodes.synthetic(id) {
+			Some(Synthetic::Selection(var) | Synthetic::Assignment(var, _)) => Some(var),
+			_ => None,
+		}));
+
+		vars.sort_unstable();
+		vars.dedup();
+	}
+
+	fn write_vars<W: Write>(&self, vars: &[Var], out: &mut W) -> Result {
+		for &var in vars {
+			self.write_line(out, &format!("int {} = 0;", var_name(var)))?;
+		}
+
+		Ok(())
+	}
+
+	fn write_body<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth += 1;
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		self.depth -= 1;
+
+		Ok(())
+	}
+
+	fn write_loop<B, W>(
+		&mut self,
+		body: &[Statement],
+		condition: &Condition,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.write_line(out, "do {")?;
+		self.write_body(body, blocks, out)?;
+
+		let condition = match condition {
+			Condition::Always => "1".to_string(),
+			Condition::Select { selector, values } => {
+				let name = find_selector(blocks, *selector);
+
+				Self::find_condition(*selector, &name, values)
+			}
+		};
+
+		self.write_line(out, &format!("}} while ({condition});"))
+	}
+
+	fn write_switch<B, W>(
+		&mut self,
+		name: &str,
+		used: &[&Case],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.write_line(out, &format!("switch ({name}) {{"))?;
+
+		for case in used {
+			for value in &case.values {
+				self.write_line(out, &format!("case {value}:"))?;
+			}
+
+			self.write_body(&case.body, blocks, out)?;
+			self.depth += 1;
+			self.write_line(out, "break;")?;
+			self.depth -= 1;
+		}
+
+		self.write_line(out, "}")
+	}
+
+	fn write_select<B, W>(
+		&mut self,
+		selector: Selector,
+		cases: &[Case],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let name = find_selector(blocks, selector);
+		let used: Vec<_> = cases.iter().filter(|case| !case.body.is_empty()).collect();
+
+		match (used.as_slice(), cases) {
+			([], _) => Ok(()),
+			([case], _) => {
+				let condition = Self::find_condition(selector, &name, &case.values);
+
+				self.write_line(out, &format!("if ({condition}) {{"))?;
+				self.write_body(&case.body, blocks, out)?;
+				self.write_line(out, "}")
+			}
+			(_, [first, second]) => {
+				let condition = Self::find_condition(selector, &name, &first.values);
+
+				self.write_line(out, &format!("if ({condition}) {{"))?;
+				self.write_body(&first.body, blocks, out)?;
+				self.write_line(out, "} else {")?;
+				self.write_body(&second.body, blocks, out)?;
+				self.write_line(out, "}")
+			}
+			_ => self.write_switch(&name, &used, blocks, out),
+		}
+	}
+
+	fn write_statement<B, W>(
+		&mut self,
+		statement: &Statement,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		match statement {
+			Statement::Block(id) => write_lines(out, self.depth, &blocks.contents(*id)),
+			Statement::Assign { var, value } => {
+				self.write_line(out, &format!("{} = {value};", var_name(*var)))
+			}
+			Statement::Loop { body, condition } => self.write_loop(body, condition, blocks, out),
+			Statement::Select { selector, cases } => {
+				self.write_select(*selector, cases, blocks, out)
+			}
+		}
+	}
+
+	/// Writes the statements, using the blocks for the contents of original nodes.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth = 0;
+		self.write_vars(&find_vars(statements), out)?;
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		Ok(())
+	}
+
+	/// Writes the statements like [`Emitter::run`], with the entry of each region
+	/// replaced by its nodes written like [`Emitter::run_unstructured`]. The statements
+	/// should be built from the graph seen through [`Collapsed`], so that each region
+	/// is followed by the node it leaves to.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run_mixed<N, B, W>(
+		&mut self,
+		statements: &[Statement],
+		nodes: &N,
+		regions: &[Unstructured],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		N: Successors + Synthetics,
+		B: Blocks,
+		W: Write,
+	{
+		let mut vars = find_vars(statements);
+		let mut blocks = Mixed {
+			nodes,
+			regions,
+			blocks,
+		};
+
+		for region in regions {
+			Self::find_synthetic_vars(nodes, region.set(), &mut vars);
+		}
+
+		self.depth = 0;
+		self.write_vars(&vars, out)?;
+
+		for statement in statements {
+			self.write_statement(statement, &mut blocks, out)?;
+		}
+
+		Ok(())
+	}
+
+	// Writes the jump to each successor, returning at nodes with none. Successors
+	// outside the region jump to its end label instead.
+	fn write_jumps<N, B, W>(
+		&mut self,
+		nodes: &N,
+		id: usize,
+		region: Option<&Unstructured>,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		N: Successors + Synthetics,
+		B: Blocks,
+		W: Write,
+	{
+		let label = |successor: usize| match region {
+			Some(region) if !region.set[successor] => format!("end_{}", region.entry),
+			_ => format!("node_{successor}"),
+		};
+		let successors: Vec<_> = nodes.successors(id).collect();
+
+		match *successors.as_slice() {
+			[] => self.write_line(out, "return;"),
+			[successor] => self.write_line(out, &format!("goto {};", label(successor))),
+			_ => {
+				let name = match nodes.synthetic(id) {
+					Some(Synthetic::Selection(var)) => var_name(var).to_string(),
+					_ => blocks.selector(id),
+				};
+
+				self.write_line(out, &format!("switch ({name}) {{"))?;
+
+				for (index, successor) in successors.into_iter().enumerate() {
+					let value = selected_value(nodes, id, index);
+
+					self.write_line(out, &format!("case {value}: goto {};", label(successor)))?;
+				}
+
+				self.write_line(out, "default: abort();")?;
+				self.write_line(out, "}")
+			}
+		}
+	}
+
+	fn write_node<N, B, W>(
+		&mut self,
+		nodes: &N,
+		id: usize,
+		region: Option<&Unstructured>,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		N: Successors + Synthetics,
+		B: Blocks,
+		W: Write,
+	{
+		self.depth = 0;
+		self.write_line(out, &format!("node_{id}:;"))?;
+		self.depth = 1;
+
+		match nodes.synthetic(id) {
+			None => write_lines(out, self.depth, &blocks.contents(id))?,
+			Some(Synthetic::Assignment(var, value)) => {
+				self.write_line(out, &format!("{} = {value};", var_name(var)))?;
+			}
+			Some(Synthetic::NoOperation | Synthetic::Selection(_)) => {}
+		}
+
+		self.write_jumps(nodes, id, region, blocks, out)
+	}
+
+	// Writes the entry of the region first, so it is reached without a jump
+	fn write_region<N, B, W>(
+		&mut self,
+		nodes: &N,
+		region: &Unstructured,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		N: Successors + Synthetics,
+		B: Blocks,
+		W: Write,
+	{
+		let rest = region.set.ones().filter(|&id| id != region.entry);
+
+		for id in std::iter::once(region.entry).chain(rest) {
+			self.write_node(nodes, id, Some(region), blocks, out)?;
+		}
+
+		self.depth = 0;
+		self.write_line(out, &format!("end_{}:;", region.entry))
+	}
+
+	/// Writes the nodes in the set without structuring them, with a label for each
+	/// node and a `goto` for each link. Nodes without successors `return`, and links
+	/// leaving the set jump to labels the caller must provide. Jump tables end with a
+	/// call to `abort`, so the caller must include `<stdlib.h>`.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run_unstructured<N, B, W>(
+		&mut self,
+		nodes: &N,
+		set: Slice,
+		start: usize,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		N: Successors + Synthetics,
+		B: Blocks,
+		W: Write,
+	{
+		let mut vars = Vec::new();
+
+		Self::find_synthetic_vars(nodes, set, &mut vars);
+
+		self.depth = 0;
+		self.write_vars(&vars, out)?;
+		self.write_line(out, &format!("goto node_{start};"))?;
+
+		for id in set.ones() {
+			self.write_node(nodes, id, None, blocks, out)?;
+		}
+
+		Ok(())
+	}
+}
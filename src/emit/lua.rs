@@ -0,0 +1,182 @@
+use std::fmt::{Result, Write};
+
+use crate::tree::{Case, Condition, Selector, Statement};
+
+use super::{find_selector, find_vars, var_name, write_indent, write_lines, Blocks};
+
+/// This structure prints a tree as Lua 5.x source. Lua has neither `switch` nor
+/// `continue`, so loops become `repeat ... until` with the condition negated and
+/// selections become `if`/`elseif` chains. The synthetic variables used are declared
+/// as locals up front and start at `0`.
+#[derive(Default)]
+pub struct Emitter {
+	depth: usize,
+}
+
+impl Emitter {
+	/// Creates a new instance of the emitter.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { depth: 0 }
+	}
+
+	fn find_condition(name: &str, values: &[usize]) -> String {
+		let comparisons: Vec<_> = values
+			.iter()
+			.map(|value| format!("{name} == {value}"))
+			.collect();
+
+		comparisons.join(" or ")
+	}
+
+	fn find_negated_condition(name: &str, values: &[usize]) -> String {
+		match values {
+			[value] => format!("{name} ~= {value}"),
+			_ => format!("not ({})", Self::find_condition(name, values)),
+		}
+	}
+
+	fn write_body<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth += 1;
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		self.depth -= 1;
+
+		Ok(())
+	}
+
+	fn write_loop<B, W>(
+		&mut self,
+		body: &[Statement],
+		condition: &Condition,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		match condition {
+			Condition::Always => {
+				write_indent(out, self.depth)?;
+				writeln!(out, "while true do")?;
+
+				self.write_body(body, blocks, out)?;
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "end")
+			}
+			Condition::Select { selector, values } => {
+				write_indent(out, self.depth)?;
+				writeln!(out, "repeat")?;
+
+				self.write_body(body, blocks, out)?;
+
+				let name = find_selector(blocks, *selector);
+				let condition = Self::find_negated_condition(&name, values);
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "until {condition}")
+			}
+		}
+	}
+
+	// Every value has a case, so the last one can be an `else` unless a case was skipped
+	fn write_select<B, W>(
+		&mut self,
+		selector: Selector,
+		cases: &[Case],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let name = find_selector(blocks, selector);
+		let used: Vec<_> = cases.iter().filter(|case| !case.body.is_empty()).collect();
+		let has_else = used.len() > 1 && used.len() == cases.len();
+
+		for (index, case) in used.iter().enumerate() {
+			write_indent(out, self.depth)?;
+
+			if index == 0 {
+				writeln!(out, "if {} then", Self::find_condition(&name, &case.values))?;
+			} else if has_else && index == used.len() - 1 {
+				writeln!(out, "else")?;
+			} else {
+				writeln!(
+					out,
+					"elseif {} then",
+					Self::find_condition(&name, &case.values)
+				)?;
+			}
+
+			self.write_body(&case.body, blocks, out)?;
+		}
+
+		if used.is_empty() {
+			return Ok(());
+		}
+
+		write_indent(out, self.depth)?;
+		writeln!(out, "end")
+	}
+
+	fn write_statement<B, W>(
+		&mut self,
+		statement: &Statement,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		match statement {
+			Statement::Block(id) => write_lines(out, self.depth, &blocks.contents(*id)),
+			Statement::Assign { var, value } => {
+				write_indent(out, self.depth)?;
+				writeln!(out, "{} = {value}", var_name(*var))
+			}
+			Statement::Loop { body, condition } => self.write_loop(body, condition, blocks, out),
+			Statement::Select { selector, cases } => {
+				self.write_select(*selector, cases, blocks, out)
+			}
+		}
+	}
+
+	/// Writes the statements, using the blocks for the contents of original nodes.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let vars: Vec<_> = find_vars(statements).into_iter().map(var_name).collect();
+
+		self.depth = 0;
+
+		if !vars.is_empty() {
+			let values = vec!["0"; vars.len()];
+
+			writeln!(out, "local {} = {}", vars.join(", "), values.join(", "))?;
+		}
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		Ok(())
+	}
+}
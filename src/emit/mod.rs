@@ -0,0 +1,110 @@
+use std::fmt::{Result, Write};
+
+use crate::{
+	nodes::Var,
+	tree::{Condition, Selector, Statement},
+};
+
+pub mod c;
+pub mod lua;
+pub mod pseudo;
+pub mod rust;
+pub mod wat;
+
+/// The code for the original nodes of a graph, as needed by the emitters.
+pub trait Blocks {
+	/// Returns the code for an original node, with one statement per line.
+	fn contents(&mut self, id: usize) -> String;
+
+	/// Returns the expression an original node with several successors selects by. It
+	/// must evaluate to the index of the successor taken.
+	fn selector(&mut self, id: usize) -> String {
+		format!("select_{id}")
+	}
+}
+
+impl<F: FnMut(usize) -> String> Blocks for F {
+	fn contents(&mut self, id: usize) -> String {
+		self(id)
+	}
+}
+
+fn find_selector<B: Blocks>(blocks: &mut B, selector: Selector) -> String {
+	match selector {
+		Selector::Var(var) => var_name(var).to_string(),
+		Selector::Node(id) => blocks.selector(id),
+	}
+}
+
+/// Returns the name emitted for the synthetic variable. None of them is the name of a C
+/// library function, such as `exit`, which they would shadow.
+#[must_use]
+pub const fn var_name(var: Var) -> &'static str {
+	match var {
+		Var::Destination => "dest",
+		Var::Repetition => "rep",
+		Var::Branch => "branch",
+		Var::Exit => "exit_id",
+	}
+}
+
+fn add_vars(statements: &[Statement], vars: &mut Vec<Var>) {
+	for statement in statements {
+		match statement {
+			Statement::Block(_) => {}
+			Statement::Assign { var, .. } => vars.push(*var),
+			Statement::Loop { body, condition } => {
+				if let Condition::Select {
+					selector: Selector::Var(var),
+					..
+				} = condition
+				{
+					vars.push(*var);
+				}
+
+				add_vars(body, vars);
+			}
+			Statement::Select { selector, cases } => {
+				if let Selector::Var(var) = selector {
+					vars.push(*var);
+				}
+
+				for case in cases {
+					add_vars(&case.body, vars);
+				}
+			}
+		}
+	}
+}
+
+// Returns the synthetic variables used by the statements, in order.
+fn find_vars(statements: &[Statement]) -> Vec<Var> {
+	let mut vars = Vec::new();
+
+	add_vars(statements, &mut vars);
+
+	vars.sort_unstable();
+	vars.dedup();
+	vars
+}
+
+fn write_indent<W: Write>(out: &mut W, depth: usize) -> Result {
+	for _ in 0..depth {
+		out.write_char('\t')?;
+	}
+
+	Ok(())
+}
+
+fn write_lines<W: Write>(out: &mut W, depth: usize, text: &str) -> Result {
+	for line in text.lines() {
+		if line.is_empty() {
+			writeln!(out)?;
+		} else {
+			write_indent(out, depth)?;
+			writeln!(out, "{line}")?;
+		}
+	}
+
+	Ok(())
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Result, Write};
+
+use crate::{
+	nodes::Var,
+	tree::{Case, Condition, Selector, Statement},
+};
+
+use super::{find_selector, var_name, write_indent, write_lines, Blocks};
+
+/// This structure prints a tree as indented pseudo-code, with `loop { ... } while`,
+/// `if`/`else`, and `switch` statements, and the synthetic assignments inline.
+#[derive(Default)]
+pub struct Emitter {
+	depth: usize,
+}
+
+impl Emitter {
+	/// Creates a new instance of the emitter.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { depth: 0 }
+	}
+
+	fn find_condition(selector: Selector, name: &str, values: &[usize]) -> String {
+		if selector == Selector::Var(Var::Repetition) && values == [1] {
+			return name.to_string();
+		}
+
+		let comparisons: Vec<_> = values
+			.iter()
+			.map(|value| format!("{name} == {value}"))
+			.collect();
+
+		comparisons.join(" || ")
+	}
+
+	fn write_body<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth += 1;
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		self.depth -= 1;
+
+		Ok(())
+	}
+
+	fn write_loop<B, W>(
+		&mut self,
+		body: &[Statement],
+		condition: &Condition,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		write_indent(out, self.depth)?;
+		writeln!(out, "loop {{")?;
+
+		self.write_body(body, blocks, out)?;
+
+		write_indent(out, self.depth)?;
+
+		match condition {
+			Condition::Always => writeln!(out, "}}"),
+			Condition::Select { selector, values } => {
+				let name = find_selector(blocks, *selector);
+				let condition = Self::find_condition(*selector, &name, values);
+
+				writeln!(out, "}} while ({condition})")
+			}
+		}
+	}
+
+	fn write_select<B, W>(
+		&mut self,
+		selector: Selector,
+		cases: &[Case],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let name = find_selector(blocks, selector);
+		let used: Vec<_> = cases.iter().filter(|case| !case.body.is_empty()).collect();
+
+		match (used.as_slice(), cases) {
+			([], _) => Ok(()),
+			([case], _) => {
+				let condition = Self::find_condition(selector, &name, &case.values);
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "if ({condition}) {{")?;
+
+				self.write_body(&case.body, blocks, out)?;
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "}}")
+			}
+			(_, [first, second]) => {
+				let condition = Self::find_condition(selector, &name, &first.values);
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "if ({condition}) {{")?;
+
+				self.write_body(&first.body, blocks, out)?;
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "}} else {{")?;
+
+				self.write_body(&second.body, blocks, out)?;
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "}}")
+			}
+			_ => {
+				write_indent(out, self.depth)?;
+				writeln!(out, "switch ({name}) {{")?;
+
+				for case in used {
+					let values: Vec<_> = case.values.iter().map(usize::to_string).collect();
+
+					write_indent(out, self.depth)?;
+					writeln!(out, "case {}:", values.join(", "))?;
+
+					self.write_body(&case.body, blocks, out)?;
+				}
+
+				write_indent(out, self.depth)?;
+				writeln!(out, "}}")
+			}
+		}
+	}
+
+	fn write_statement<B, W>(
+		&mut self,
+		statement: &Statement,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		match statement {
+			Statement::Block(id) => write_lines(out, self.depth, &blocks.contents(*id)),
+			Statement::Assign { var, value } => {
+				write_indent(out, self.depth)?;
+				writeln!(out, "{} = {value}", var_name(*var))
+			}
+			Statement::Loop { body, condition } => self.write_loop(body, condition, blocks, out),
+			Statement::Select { selector, cases } => {
+				self.write_select(*selector, cases, blocks, out)
+			}
+		}
+	}
+
+	/// Writes the statements, using the blocks for the contents of original nodes.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth = 0;
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		Ok(())
+	}
+}
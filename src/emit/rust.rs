@@ -0,0 +1,230 @@
+use std::fmt::{Result, Write};
+
+use crate::tree::{Case, Condition, Selector, Statement};
+
+use super::{find_selector, find_vars, var_name, write_indent, write_lines, Blocks};
+
+/// This structure prints a tree as the body of a Rust function. Loops become labeled
+/// `loop` with a `break` out of them, selections with one or two arms become `if`, and
+/// the rest become `match`. The synthetic variables used are declared as mutable
+/// `usize` locals up front.
+///
+/// Selectors must evaluate to the index of the successor taken as a `usize`.
+#[derive(Default)]
+pub struct Emitter {
+	depth: usize,
+	labels: usize,
+}
+
+impl Emitter {
+	/// Creates a new instance of the emitter.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			depth: 0,
+			labels: 0,
+		}
+	}
+
+	fn find_pattern(values: &[usize]) -> String {
+		let values: Vec<_> = values.iter().map(usize::to_string).collect();
+
+		values.join(" | ")
+	}
+
+	fn find_condition(name: &str, values: &[usize]) -> String {
+		match values {
+			[value] => format!("{name} == {value}"),
+			_ => format!("matches!({name}, {})", Self::find_pattern(values)),
+		}
+	}
+
+	fn find_negated_condition(name: &str, values: &[usize]) -> String {
+		match values {
+			[value] => format!("{name} != {value}"),
+			_ => format!("!matches!({name}, {})", Self::find_pattern(values)),
+		}
+	}
+
+	fn write_line<W: Write>(&self, out: &mut W, line: &str) -> Result {
+		write_indent(out, self.depth)?;
+		writeln!(out, "{line}")
+	}
+
+	fn write_body<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth += 1;
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		self.depth -= 1;
+
+		Ok(())
+	}
+
+	fn write_loop<B, W>(
+		&mut self,
+		body: &[Statement],
+		condition: &Condition,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let Condition::Select { selector, values } = condition else {
+			self.write_line(out, "loop {")?;
+			self.write_body(body, blocks, out)?;
+
+			return self.write_line(out, "}");
+		};
+
+		let label = format!("'loop{}", self.labels);
+
+		self.labels += 1;
+
+		self.write_line(out, &format!("{label}: loop {{"))?;
+		self.write_body(body, blocks, out)?;
+		self.depth += 1;
+
+		let name = find_selector(blocks, *selector);
+		let condition = Self::find_negated_condition(&name, values);
+
+		self.write_line(out, &format!("if {condition} {{"))?;
+		self.depth += 1;
+		self.write_line(out, &format!("break {label};"))?;
+		self.depth -= 1;
+		self.write_line(out, "}")?;
+		self.depth -= 1;
+		self.write_line(out, "}")
+	}
+
+	// Every value has a case, so the last one can be the wildcard unless a case was
+	// skipped
+	fn write_match<B, W>(
+		&mut self,
+		name: &str,
+		used: &[&Case],
+		has_wildcard: bool,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.write_line(out, &format!("match {name} {{"))?;
+		self.depth += 1;
+
+		for (index, case) in used.iter().enumerate() {
+			let pattern = if has_wildcard && index == used.len() - 1 {
+				"_".to_string()
+			} else {
+				Self::find_pattern(&case.values)
+			};
+
+			self.write_line(out, &format!("{pattern} => {{"))?;
+			self.write_body(&case.body, blocks, out)?;
+			self.write_line(out, "}")?;
+		}
+
+		if !has_wildcard {
+			self.write_line(out, "_ => {}")?;
+		}
+
+		self.depth -= 1;
+		self.write_line(out, "}")
+	}
+
+	fn write_select<B, W>(
+		&mut self,
+		selector: Selector,
+		cases: &[Case],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let name = find_selector(blocks, selector);
+		let used: Vec<_> = cases.iter().filter(|case| !case.body.is_empty()).collect();
+
+		match (used.as_slice(), cases) {
+			([], _) => Ok(()),
+			([case], _) => {
+				let condition = Self::find_condition(&name, &case.values);
+
+				self.write_line(out, &format!("if {condition} {{"))?;
+				self.write_body(&case.body, blocks, out)?;
+				self.write_line(out, "}")
+			}
+			(_, [first, second]) => {
+				let condition = Self::find_condition(&name, &first.values);
+
+				self.write_line(out, &format!("if {condition} {{"))?;
+				self.write_body(&first.body, blocks, out)?;
+				self.write_line(out, "} else {")?;
+				self.write_body(&second.body, blocks, out)?;
+				self.write_line(out, "}")
+			}
+			_ => {
+				let has_wildcard = used.len() == cases.len();
+
+				self.write_match(&name, &used, has_wildcard, blocks, out)
+			}
+		}
+	}
+
+	fn write_statement<B, W>(
+		&mut self,
+		statement: &Statement,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		match statement {
+			Statement::Block(id) => write_lines(out, self.depth, &blocks.contents(*id)),
+			Statement::Assign { var, value } => {
+				self.write_line(out, &format!("{} = {value};", var_name(*var)))
+			}
+			Statement::Loop { body, condition } => self.write_loop(body, condition, blocks, out),
+			Statement::Select { selector, cases } => {
+				self.write_select(*selector, cases, blocks, out)
+			}
+		}
+	}
+
+	/// Writes the statements, using the blocks for the contents of original nodes.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth = 0;
+		self.labels = 0;
+
+		for var in find_vars(statements) {
+			self.write_line(out, &format!("let mut {}: usize = 0;", var_name(var)))?;
+		}
+
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,319 @@
+use std::fmt::{Result, Write};
+
+use crate::tree::{Case, Condition, Selector, Statement};
+
+use super::{find_vars, var_name, write_indent, write_lines, Blocks};
+
+// Returns whether a condition compares a node selector against several values, so it
+// must be kept in a local instead of being run again
+const fn is_shared(selector: Selector, values: &[usize]) -> bool {
+	matches!((selector, values), (Selector::Node(_), [_, _, ..]))
+}
+
+// Returns whether any condition in the statements needs the `$select` local
+fn has_shared(statements: &[Statement]) -> bool {
+	statements.iter().any(|statement| match statement {
+		Statement::Block(_) | Statement::Assign { .. } => false,
+		Statement::Loop { body, condition } => {
+			let is_condition_shared = match condition {
+				Condition::Always => false,
+				Condition::Select { selector, values } => is_shared(*selector, values),
+			};
+
+			is_condition_shared || has_shared(body)
+		}
+		Statement::Select { selector, cases } => cases
+			.iter()
+			.any(|case| is_shared(*selector, &case.values) || has_shared(&case.body)),
+	})
+}
+
+/// This structure prints a tree as the body of a WebAssembly function in the text
+/// format. Loops become `loop` with a `br_if` back to the start, selections with one or
+/// two arms become `if`, and the rest become nested `block` with a `br_table`. The
+/// synthetic variables used are declared as `i32` locals up front.
+///
+/// Block contents and selectors are written as plain instructions, and a selector must
+/// leave the index of the successor taken on the stack as an `i32`. A selector compared
+/// against several values is run once and kept in the `$select` local.
+#[derive(Default)]
+pub struct Emitter {
+	depth: usize,
+	labels: usize,
+}
+
+impl Emitter {
+	/// Creates a new instance of the emitter.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			depth: 0,
+			labels: 0,
+		}
+	}
+
+	fn add_label(&mut self, name: &str) -> String {
+		let label = format!("${name}{}", self.labels);
+
+		self.labels += 1;
+
+		label
+	}
+
+	fn write_line<W: Write>(&self, out: &mut W, line: &str) -> Result {
+		write_indent(out, self.depth)?;
+		writeln!(out, "{line}")
+	}
+
+	fn write_selector<B, W>(&self, selector: Selector, blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		match selector {
+			Selector::Var(var) => self.write_line(out, &format!("local.get ${}", var_name(var))),
+			Selector::Node(id) => write_lines(out, self.depth, &blocks.selector(id)),
+		}
+	}
+
+	// Leaves `1` on the stack if the selector has any of the values and `0` otherwise
+	fn write_condition<B, W>(
+		&self,
+		selector: Selector,
+		values: &[usize],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let is_shared = is_shared(selector, values);
+
+		if is_shared {
+			self.write_selector(selector, blocks, out)?;
+			self.write_line(out, "local.set $select")?;
+		}
+
+		for (index, value) in values.iter().enumerate() {
+			if is_shared {
+				self.write_line(out, "local.get $select")?;
+			} else {
+				self.write_selector(selector, blocks, out)?;
+			}
+
+			self.write_line(out, &format!("i32.const {value}"))?;
+			self.write_line(out, "i32.eq")?;
+
+			if index != 0 {
+				self.write_line(out, "i32.or")?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn write_statements<B, W>(
+		&mut self,
+		statements: &[Statement],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		for statement in statements {
+			self.write_statement(statement, blocks, out)?;
+		}
+
+		Ok(())
+	}
+
+	fn write_body<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth += 1;
+		self.write_statements(statements, blocks, out)?;
+		self.depth -= 1;
+
+		Ok(())
+	}
+
+	fn write_loop<B, W>(
+		&mut self,
+		body: &[Statement],
+		condition: &Condition,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let label = self.add_label("loop");
+
+		self.write_line(out, &format!("loop {label}"))?;
+		self.write_body(body, blocks, out)?;
+		self.depth += 1;
+
+		match condition {
+			Condition::Always => self.write_line(out, &format!("br {label}"))?,
+			Condition::Select { selector, values } => {
+				self.write_condition(*selector, values, blocks, out)?;
+				self.write_line(out, &format!("br_if {label}"))?;
+			}
+		}
+
+		self.depth -= 1;
+		self.write_line(out, "end")
+	}
+
+	fn write_if<B, W>(
+		&mut self,
+		selector: Selector,
+		then: &Case,
+		otherwise: Option<&Case>,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.write_condition(selector, &then.values, blocks, out)?;
+		self.write_line(out, "if")?;
+		self.write_body(&then.body, blocks, out)?;
+
+		if let Some(otherwise) = otherwise {
+			self.write_line(out, "else")?;
+			self.write_body(&otherwise.body, blocks, out)?;
+		}
+
+		self.write_line(out, "end")
+	}
+
+	// Each case is placed after the end of its own block, so that branching to that
+	// block runs it. Values of skipped cases branch past all of them.
+	fn write_table<B, W>(
+		&mut self,
+		selector: Selector,
+		used: &[&Case],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let end = self.add_label("end");
+		let labels: Vec<_> = used.iter().map(|_| self.add_label("case")).collect();
+		let count = used
+			.iter()
+			.flat_map(|case| case.values.iter())
+			.max()
+			.map_or(0, |&value| value + 1);
+
+		let mut table = vec![end.as_str(); count];
+
+		for (case, label) in used.iter().zip(&labels) {
+			for &value in &case.values {
+				table[value] = label;
+			}
+		}
+
+		self.write_line(out, &format!("block {end}"))?;
+
+		for label in labels.iter().rev() {
+			self.depth += 1;
+			self.write_line(out, &format!("block {label}"))?;
+		}
+
+		self.depth += 1;
+		self.write_selector(selector, blocks, out)?;
+		self.write_line(out, &format!("br_table {} {end}", table.join(" ")))?;
+
+		for (index, case) in used.iter().enumerate() {
+			self.depth -= 1;
+			self.write_line(out, "end")?;
+			self.write_statements(&case.body, blocks, out)?;
+
+			if index + 1 != used.len() {
+				self.write_line(out, &format!("br {end}"))?;
+			}
+		}
+
+		self.depth -= 1;
+		self.write_line(out, "end")
+	}
+
+	fn write_select<B, W>(
+		&mut self,
+		selector: Selector,
+		cases: &[Case],
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		let used: Vec<_> = cases.iter().filter(|case| !case.body.is_empty()).collect();
+
+		match (used.as_slice(), cases) {
+			([], _) => Ok(()),
+			([case], _) => self.write_if(selector, case, None, blocks, out),
+			(_, [first, second]) => self.write_if(selector, first, Some(second), blocks, out),
+			_ => self.write_table(selector, &used, blocks, out),
+		}
+	}
+
+	fn write_statement<B, W>(
+		&mut self,
+		statement: &Statement,
+		blocks: &mut B,
+		out: &mut W,
+	) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		match statement {
+			Statement::Block(id) => write_lines(out, self.depth, &blocks.contents(*id)),
+			Statement::Assign { var, value } => {
+				self.write_line(out, &format!("i32.const {value}"))?;
+				self.write_line(out, &format!("local.set ${}", var_name(*var)))
+			}
+			Statement::Loop { body, condition } => self.write_loop(body, condition, blocks, out),
+			Statement::Select { selector, cases } => {
+				self.write_select(*selector, cases, blocks, out)
+			}
+		}
+	}
+
+	/// Writes the statements, using the blocks for the contents of original nodes.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	pub fn run<B, W>(&mut self, statements: &[Statement], blocks: &mut B, out: &mut W) -> Result
+	where
+		B: Blocks,
+		W: Write,
+	{
+		self.depth = 0;
+		self.labels = 0;
+
+		for var in find_vars(statements) {
+			self.write_line(out, &format!("(local ${} i32)", var_name(var)))?;
+		}
+
+		if has_shared(statements) {
+			self.write_line(out, "(local $select i32)")?;
+		}
+
+		self.write_statements(statements, blocks, out)
+	}
+}
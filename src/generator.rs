@@ -0,0 +1,249 @@
+use crate::{
+	list::{Instruction, List},
+	nodes::{Nodes, Predecessors, Successors},
+};
+
+/// The shape of the graphs made by a [`Generator`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Parameters {
+	/// The number of nodes, including the entry and exits.
+	pub nodes: usize,
+
+	/// The chance of each extra edge going backwards instead of forwards.
+	pub back_edges: f64,
+
+	/// The largest number of successors a node may have. It is exceeded only when
+	/// there is no other way to reach every node, such as with a value of `1`.
+	pub max_degree: usize,
+
+	/// The chance of each backward edge getting a second entry into its cycle. Backward
+	/// edges to nodes that do not surely reach them are left alone.
+	pub irreducibility: f64,
+
+	/// The number of nodes without successors.
+	pub exits: usize,
+}
+
+impl Default for Parameters {
+	fn default() -> Self {
+		Self {
+			nodes: 64,
+			back_edges: 1.0 / 12.0,
+			max_degree: 2,
+			irreducibility: 0.0,
+			exits: 1,
+		}
+	}
+}
+
+/// The ways parameters can leave no room for a graph.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum Error {
+	/// There are no exits.
+	NoExits,
+
+	/// The exits leave no room for the entry.
+	TooManyExits { exits: usize, nodes: usize },
+
+	/// Nodes may not have any successor.
+	NoDegree,
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NoExits => write!(f, "graphs need at least one exit"),
+			Self::TooManyExits { exits, nodes } => {
+				write!(
+					f,
+					"{exits} exits leave no room for an entry in {nodes} nodes"
+				)
+			}
+			Self::NoDegree => write!(f, "nodes need room for at least one successor"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// This structure generates random control flow graphs from a seed. The same seed and
+/// parameters always produce the same graph, with node `0` as the only entry.
+pub struct Generator {
+	state: u64,
+}
+
+impl Generator {
+	/// Creates a new generator from the seed.
+	#[must_use]
+	pub const fn new(seed: u64) -> Self {
+		Self { state: seed }
+	}
+
+	// Resources:
+	// "Fast Splittable Pseudorandom Number Generators",
+	//     by Guy L. Steele Jr., Doug Lea, and Christine H. Flood
+	fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+		let mut value = self.state;
+
+		value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+		value ^ (value >> 31)
+	}
+
+	fn next_below(&mut self, bound: usize) -> usize {
+		let bound = u64::try_from(bound).unwrap();
+		let value = ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64;
+
+		usize::try_from(value).unwrap()
+	}
+
+	fn next_in(&mut self, start: usize, end: usize) -> usize {
+		start + self.next_below(end - start)
+	}
+
+	fn next_chance(&mut self, chance: f64) -> bool {
+		#[allow(clippy::cast_precision_loss)]
+		let value = (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64;
+
+		value < chance
+	}
+
+	// Returns the nodes after the ancestor on the path of first predecessors leading to
+	// the node, which is empty if the path does not pass through the ancestor
+	fn find_path_from(list: &List, ancestor: usize, mut id: usize) -> Vec<usize> {
+		let mut path = Vec::new();
+
+		// First predecessors always come earlier, so the path only goes down
+		while id > ancestor {
+			path.push(id);
+
+			id = list.predecessors(id).next().unwrap();
+		}
+
+		if id != ancestor {
+			path.clear();
+		}
+
+		path
+	}
+
+	fn add_back_edge(&mut self, list: &mut List, id: usize, parameters: &Parameters) {
+		let target = self.next_in(1, id + 1);
+
+		list.add_link(id, target);
+
+		// A node before the cycle jumps into its middle, adding a second entry. The
+		// target only surely reaches the node when it is one of its first predecessors.
+		if target + 1 < id && self.next_chance(parameters.irreducibility) {
+			let cycle = Self::find_path_from(list, target, id);
+
+			if cycle.is_empty() {
+				return;
+			}
+
+			let from = self.next_below(target);
+			let to = cycle[self.next_below(cycle.len())];
+
+			if list.successors(from).count() < parameters.max_degree {
+				list.add_link(from, to);
+			}
+		}
+	}
+
+	fn add_predecessors(&mut self, list: &mut List, parameters: &Parameters) {
+		let first_exit = parameters.nodes - parameters.exits;
+		let mut open = Vec::with_capacity(first_exit);
+
+		for id in 1..parameters.nodes {
+			if id <= first_exit {
+				open.push(id - 1);
+			}
+
+			// Nodes that are full are only chosen when no other node is left
+			if open.is_empty() {
+				list.add_link(id.min(first_exit) - 1, id);
+
+				continue;
+			}
+
+			let index = self.next_below(open.len());
+			let predecessor = open[index];
+
+			list.add_link(predecessor, id);
+
+			if list.successors(predecessor).count() >= parameters.max_degree {
+				open.swap_remove(index);
+			}
+		}
+	}
+
+	fn add_successors(&mut self, list: &mut List, parameters: &Parameters) {
+		let len = parameters.nodes;
+
+		for id in 0..len - parameters.exits {
+			let mut degree = list.successors(id).count();
+
+			if degree == 0 {
+				let successor = self.next_in(id + 1, len);
+
+				list.add_link(id, successor);
+
+				degree = 1;
+			}
+
+			let wanted = self.next_in(degree, parameters.max_degree.max(degree) + 1);
+
+			for _ in degree..wanted {
+				if id != 0 && self.next_chance(parameters.back_edges) {
+					self.add_back_edge(list, id, parameters);
+				} else {
+					let successor = self.next_in(id + 1, len);
+
+					list.add_link(id, successor);
+				}
+			}
+		}
+	}
+
+	/// Generates a new graph with the given parameters.
+	///
+	/// # Errors
+	///
+	/// Returns an error if there is no room for the entry, at least one exit, and one
+	/// successor per node.
+	pub fn run(&mut self, parameters: &Parameters) -> Result<List, Error> {
+		let len = parameters.nodes;
+
+		if parameters.exits == 0 {
+			return Err(Error::NoExits);
+		}
+
+		if parameters.exits >= len {
+			return Err(Error::TooManyExits {
+				exits: parameters.exits,
+				nodes: len,
+			});
+		}
+
+		if parameters.max_degree == 0 {
+			return Err(Error::NoDegree);
+		}
+
+		let mut list = List::with_capacity(len);
+
+		for _ in 0..len {
+			list.add_instruction(Instruction::Simple);
+		}
+
+		// Every node is reached from an earlier one that is not an exit
+		self.add_predecessors(&mut list, parameters);
+
+		// Every node that is not an exit moves forward, so all of them reach an exit
+		self.add_successors(&mut list, parameters);
+
+		Ok(list)
+	}
+}
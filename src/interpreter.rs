@@ -0,0 +1,148 @@
+use crate::{
+	nodes::{Successors, Synthetic, Synthetics, Var},
+	tree::selected_value,
+};
+
+/// The ways running a graph can go wrong.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum Error {
+	/// The selection has no successor for the value of its variable.
+	NoSuccessor { id: usize, value: usize },
+
+	/// The synthetic node is part of a cycle that never reaches an original node.
+	Diverged(usize),
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NoSuccessor { id, value } => {
+				write!(f, "selection at node {id} has no successor for {value}")
+			}
+			Self::Diverged(id) => write!(f, "node {id} loops without reaching an original node"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// This structure runs a graph from its start, recording the original nodes visited.
+/// Original nodes with several successors ask a chooser for the index of the one to
+/// take, while synthetic nodes follow their variables, which all start at `0`.
+///
+/// Restructuring must not change which original nodes run, so a graph and its
+/// restructured form should give the same trace when asked the same choices.
+#[derive(Default)]
+pub struct Interpreter {
+	trace: Vec<usize>,
+	vars: [usize; 4],
+	states: Vec<(usize, [usize; 4])>,
+}
+
+impl Interpreter {
+	/// Creates a new instance of the interpreter.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			trace: Vec::new(),
+			vars: [0; 4],
+			states: Vec::new(),
+		}
+	}
+
+	/// Returns the original nodes visited, as of the last run.
+	#[must_use]
+	pub fn trace(&self) -> &[usize] {
+		&self.trace
+	}
+
+	const fn index_of(var: Var) -> usize {
+		match var {
+			Var::Destination => 0,
+			Var::Repetition => 1,
+			Var::Branch => 2,
+			Var::Exit => 3,
+		}
+	}
+
+	fn find_selected<N>(&self, nodes: &N, id: usize, var: Var) -> Result<usize, Error>
+	where
+		N: Successors + Synthetics,
+	{
+		let value = self.vars[Self::index_of(var)];
+
+		(0..nodes.successors(id).count())
+			.find(|&index| selected_value(nodes, id, index) == value)
+			.ok_or(Error::NoSuccessor { id, value })
+	}
+
+	// Returns the index of the successor taken, with a synthetic node seen again in
+	// the same state meaning no original node will ever be reached
+	fn step<N, C>(&mut self, nodes: &N, id: usize, choose: &mut C) -> Result<usize, Error>
+	where
+		N: Successors + Synthetics,
+		C: FnMut(usize, usize) -> usize,
+	{
+		let Some(synthetic) = nodes.synthetic(id) else {
+			let len = nodes.successors(id).count();
+
+			self.trace.push(id);
+			self.states.clear();
+
+			return Ok(if len > 1 { choose(id, len) } else { 0 });
+		};
+
+		let state = (id, self.vars);
+
+		if self.states.contains(&state) {
+			return Err(Error::Diverged(id));
+		}
+
+		self.states.push(state);
+
+		match synthetic {
+			Synthetic::NoOperation => Ok(0),
+			Synthetic::Selection(var) => self.find_selected(nodes, id, var),
+			Synthetic::Assignment(var, value) => {
+				self.vars[Self::index_of(var)] = value;
+
+				Ok(0)
+			}
+		}
+	}
+
+	/// Runs the nodes from the start until a node without successors is reached or
+	/// the limit of original nodes is visited. The chooser is given an original node
+	/// and its number of successors, and returns the index of the one to take.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a selection cannot pick a successor or the run gets stuck
+	/// in synthetic nodes.
+	pub fn run<N, C>(
+		&mut self,
+		nodes: &N,
+		mut start: usize,
+		limit: usize,
+		mut choose: C,
+	) -> Result<(), Error>
+	where
+		N: Successors + Synthetics,
+		C: FnMut(usize, usize) -> usize,
+	{
+		self.trace.clear();
+		self.vars = [0; 4];
+		self.states.clear();
+
+		while self.trace.len() < limit {
+			let index = self.step(nodes, start, &mut choose)?;
+
+			match nodes.successors(start).nth(index) {
+				Some(successor) => start = successor,
+				None => break,
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -1,5 +1,15 @@
 pub mod directed;
+pub mod dot;
+pub mod emit;
+#[cfg(feature = "generator")]
+pub mod generator;
+pub mod interpreter;
+pub mod list;
+pub mod merge;
+pub mod minimizer;
 pub mod nodes;
+pub mod pruner;
 pub mod structurer;
+pub mod tree;
 
 pub use set;
@@ -0,0 +1,391 @@
+use crate::{
+	nodes::{EdgeKind, Nodes, Predecessors, Successors, Synthetic, Synthetics, Var},
+	set::Set,
+};
+
+/// The contents of a node in a [`List`].
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Instruction {
+	NoOperation,
+	Simple,
+	Selection { var: Var },
+	SetVariable { var: Var, value: usize },
+}
+
+impl Instruction {
+	/// Returns whether the instruction was added by the structurer.
+	#[must_use]
+	pub const fn is_synthetic(&self) -> bool {
+		matches!(
+			self,
+			Self::NoOperation | Self::Selection { .. } | Self::SetVariable { .. }
+		)
+	}
+}
+
+impl std::fmt::Debug for Instruction {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NoOperation => write!(f, "No Operation"),
+			Self::Simple => write!(f, "Simple"),
+			Self::Selection { var } => write!(f, "Selection {var:?}"),
+			Self::SetVariable { var, value } => write!(f, "{var:?} := {value}"),
+		}
+	}
+}
+
+// The kind of each link is kept alongside its successor
+#[derive(Clone, PartialEq, Eq)]
+struct Node {
+	predecessors: Vec<usize>,
+	successors: Vec<usize>,
+	kinds: Vec<EdgeKind>,
+	instruction: Instruction,
+}
+
+/// A simple control flow graph stored as adjacency lists. Links are
+/// [`EdgeKind::Normal`] unless added with [`List::add_link_with_kind`].
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct List {
+	nodes: Vec<Node>,
+}
+
+impl std::fmt::Debug for List {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		const NODE_ATTRIBUTES: &str = r##"shape = plain, style = filled, fillcolor = "#DDDDFF""##;
+
+		writeln!(f, "digraph {{")?;
+		writeln!(f, "\tstyle = filled;")?;
+		writeln!(f, "\tnode [{NODE_ATTRIBUTES}];")?;
+
+		for (id, node) in self.nodes.iter().enumerate() {
+			for &predecessor in &node.predecessors {
+				writeln!(f, "\tnode_{predecessor} -> node_{id};")?;
+			}
+
+			write!(f, "\tnode_{id} [label=\"NODE {id}\\l")?;
+
+			node.instruction.fmt(f)?;
+
+			write!(f, "\"")?;
+
+			if node.instruction.is_synthetic() {
+				write!(f, ", fillcolor = \"#FFDDDD\"")?;
+			}
+
+			writeln!(f, "];")?;
+		}
+
+		writeln!(f, "}}")
+	}
+}
+
+impl List {
+	#[must_use]
+	pub fn with_capacity(capacity: usize) -> Self {
+		let nodes = Vec::with_capacity(capacity);
+
+		Self { nodes }
+	}
+
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	#[must_use]
+	pub fn instruction(&self, id: usize) -> &Instruction {
+		&self.nodes[id].instruction
+	}
+
+	#[must_use]
+	pub fn ids(&self) -> Set {
+		(0..self.nodes.len()).collect()
+	}
+
+	/// Creates a list of simple nodes joined by the given links, which are added in order.
+	#[must_use]
+	pub fn from_edges(len: usize, edges: &[(usize, usize)]) -> Self {
+		let mut list = Self::with_capacity(len);
+
+		for _ in 0..len {
+			list.add_instruction(Instruction::Simple);
+		}
+
+		for &(from, to) in edges {
+			list.add_link(from, to);
+		}
+
+		list
+	}
+
+	pub fn add_instruction(&mut self, instruction: Instruction) -> usize {
+		let node = Node {
+			predecessors: Vec::new(),
+			successors: Vec::new(),
+			kinds: Vec::new(),
+			instruction,
+		};
+
+		self.nodes.push(node);
+		self.nodes.len() - 1
+	}
+
+	/// Adds a new link from the `from` node to the `to` node of the given kind.
+	pub fn add_link_with_kind(&mut self, from: usize, to: usize, kind: EdgeKind) {
+		self.nodes[from].successors.push(to);
+		self.nodes[from].kinds.push(kind);
+		self.nodes[to].predecessors.push(from);
+	}
+
+	/// Returns the kind of each link leaving the node, in the order of its successors.
+	#[must_use]
+	pub fn edge_kinds(&self, id: usize) -> &[EdgeKind] {
+		&self.nodes[id].kinds
+	}
+}
+
+impl Predecessors for List {
+	fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.nodes[id].predecessors.iter().copied()
+	}
+}
+
+impl Successors for List {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.nodes[id].successors.iter().copied()
+	}
+}
+
+impl Nodes for List {
+	fn has_assignment(&self, id: usize, var: Var) -> bool {
+		if let Instruction::SetVariable { var: old, .. } = self.nodes[id].instruction {
+			old == var
+		} else {
+			false
+		}
+	}
+
+	fn add_no_operation(&mut self) -> usize {
+		self.add_instruction(Instruction::NoOperation)
+	}
+
+	fn add_variable(&mut self, var: Var, value: usize) -> usize {
+		self.add_instruction(Instruction::SetVariable { var, value })
+	}
+
+	fn add_selection(&mut self, var: Var) -> usize {
+		self.add_instruction(Instruction::Selection { var })
+	}
+
+	fn add_link(&mut self, from: usize, to: usize) {
+		self.add_link_with_kind(from, to, EdgeKind::Normal);
+	}
+
+	fn replace_link(&mut self, from: usize, to: usize, new: usize) {
+		let successor = self.nodes[from]
+			.successors
+			.iter()
+			.position(|&id| id == to)
+			.unwrap();
+
+		self.nodes[from].successors[successor] = new;
+		self.nodes[new].predecessors.push(from);
+
+		let predecessor = self.nodes[to]
+			.predecessors
+			.iter()
+			.position(|&id| id == from)
+			.unwrap();
+
+		self.nodes[to].predecessors.remove(predecessor);
+	}
+
+	fn remove_link(&mut self, from: usize, to: usize) {
+		let successor = self.nodes[from]
+			.successors
+			.iter()
+			.position(|&id| id == to)
+			.unwrap();
+
+		self.nodes[from].successors.remove(successor);
+		self.nodes[from].kinds.remove(successor);
+
+		let predecessor = self.nodes[to]
+			.predecessors
+			.iter()
+			.position(|&id| id == from)
+			.unwrap();
+
+		self.nodes[to].predecessors.remove(predecessor);
+	}
+
+	// Parallel links may differ in kind, so the one that restricts the restructurers
+	// most is taken, as no link of another kind can be told apart from it
+	fn edge_kind(&self, from: usize, to: usize) -> EdgeKind {
+		let node = &self.nodes[from];
+		let rank = |kind: EdgeKind| match kind {
+			EdgeKind::Normal => 0,
+			EdgeKind::Ignored => 1,
+			EdgeKind::Pinned => 2,
+		};
+
+		node.successors
+			.iter()
+			.zip(&node.kinds)
+			.filter(|&(&id, _)| id == to)
+			.map(|(_, &kind)| kind)
+			.max_by_key(|&kind| rank(kind))
+			.unwrap_or_default()
+	}
+}
+
+impl Synthetics for List {
+	fn synthetic(&self, id: usize) -> Option<Synthetic> {
+		match self.nodes[id].instruction {
+			Instruction::NoOperation => Some(Synthetic::NoOperation),
+			Instruction::Simple => None,
+			Instruction::Selection { var } => Some(Synthetic::Selection(var)),
+			Instruction::SetVariable { var, value } => Some(Synthetic::Assignment(var, value)),
+		}
+	}
+}
+
+#[cfg(feature = "arbitrary")]
+impl arbitrary::Arbitrary<'_> for List {
+	fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> Result<Self, arbitrary::Error> {
+		let len = u.arbitrary_len::<u64>()?.max(2);
+		let mut list = Self::with_capacity(len);
+
+		for id in 0..len {
+			list.add_instruction(Instruction::Simple);
+
+			if let Some(last) = id.checked_sub(1) {
+				list.add_link(last, id);
+			}
+		}
+
+		for _ in 0..u.arbitrary_len::<(usize, usize)>()? {
+			let a = u.choose_index(list.nodes.len())?.max(1);
+			let b = u.choose_index(list.nodes.len())?.max(1);
+
+			if u.ratio(11, 12)? {
+				list.add_link(a.min(b), a.max(b));
+			} else {
+				list.add_link(a.max(b), a.min(b));
+			}
+		}
+
+		Ok(list)
+	}
+}
+
+/// The version of the JSON schema of a [`List`], written to its `version` field. A
+/// list is only read back if its version is at most this one. Version 1 has no edge
+/// kinds, so all of its links are [`EdgeKind::Normal`].
+#[cfg(feature = "serde")]
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[cfg(feature = "serde")]
+fn is_normal(kinds: &&[EdgeKind]) -> bool {
+	kinds.iter().all(|&kind| kind == EdgeKind::Normal)
+}
+
+// Predecessors are left out, as they are found again from the successors, and so are
+// the kinds of links when all of them are normal
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct NodeRef<'a> {
+	instruction: &'a Instruction,
+	successors: &'a [usize],
+	#[serde(skip_serializing_if = "is_normal")]
+	kinds: &'a [EdgeKind],
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct NodeData {
+	instruction: Instruction,
+	successors: Vec<usize>,
+	#[serde(default)]
+	kinds: Vec<EdgeKind>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for List {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		use serde::ser::SerializeStruct;
+
+		let nodes: Vec<_> = self
+			.nodes
+			.iter()
+			.map(|node| NodeRef {
+				instruction: &node.instruction,
+				successors: &node.successors,
+				kinds: &node.kinds,
+			})
+			.collect();
+
+		let mut state = serializer.serialize_struct("List", 2)?;
+
+		state.serialize_field("version", &SCHEMA_VERSION)?;
+		state.serialize_field("nodes", &nodes)?;
+		state.end()
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for List {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		#[derive(serde::Deserialize)]
+		struct ListData {
+			version: u32,
+			nodes: Vec<NodeData>,
+		}
+
+		let ListData { version, nodes } = ListData::deserialize(deserializer)?;
+
+		if !(1..=SCHEMA_VERSION).contains(&version) {
+			return Err(serde::de::Error::custom(format!(
+				"schema version {version} is not supported, expected at most {SCHEMA_VERSION}"
+			)));
+		}
+
+		let mut list = Self::with_capacity(nodes.len());
+
+		for node in &nodes {
+			list.add_instruction(node.instruction.clone());
+		}
+
+		for (id, node) in nodes.iter().enumerate() {
+			if !node.kinds.is_empty() && node.kinds.len() != node.successors.len() {
+				return Err(serde::de::Error::custom(format!(
+					"node {id} has {} edge kinds for {} successors",
+					node.kinds.len(),
+					node.successors.len()
+				)));
+			}
+
+			for (index, &successor) in node.successors.iter().enumerate() {
+				if successor >= nodes.len() {
+					return Err(serde::de::Error::custom(format!(
+						"successor {successor} of node {id} is out of bounds"
+					)));
+				}
+
+				let kind = node.kinds.get(index).copied().unwrap_or_default();
+
+				list.add_link_with_kind(id, successor, kind);
+			}
+		}
+
+		Ok(list)
+	}
+}
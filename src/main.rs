@@ -0,0 +1,569 @@
+use std::{
+	collections::BTreeMap,
+	fmt::Write as _,
+	io::{Read, Write},
+	panic::AssertUnwindSafe,
+	process::ExitCode,
+	str::FromStr,
+};
+
+use perfect_reconstructibility::{
+	dot::{Reader, Writer},
+	emit::{c, lua, pseudo, rust, wat, Blocks},
+	generator::{self, Generator, Parameters},
+	interpreter::Interpreter,
+	list::List,
+	merge::Checker,
+	minimizer::Minimizer,
+	pruner::Policy,
+	structurer::{
+		driver::{self, Driver},
+		observer::StructureObserver,
+		statistics::Statistics,
+	},
+	tree::Builder,
+};
+
+const USAGE: &str = "\
+usage: perfect-reconstructibility <command> [options] [file]
+
+Graphs are read from the file, or standard input if missing, as DOT, JSON, or a
+fuzzer artifact. Node 0 is the entry of every graph.
+
+commands:
+    structure  restructure the graph and write it [--from dot|json] [--to dot|json]
+    verify     check that the graph is structured [--from dot|json] [--spirv]
+    stats      restructure the graph and count what was added [--from dot|json]
+    emit       restructure the graph and print it as code [--from dot|json]
+               [--lang pseudo|lua|wat|c|rust]
+    minimize   shrink a graph that fails to restructure and write it
+               [--from dot|json|artifact] [--to dot|json] [--check panic|verify|trace]
+    gen        write a random graph [--to dot|json] [--seed N] [--nodes N]
+               [--back-edges F] [--max-degree N] [--irreducibility F] [--exits N]
+
+Commands that restructure take --unify-exits to join the nodes without successors
+into one exit first, instead of restructuring each exit separately. They fail on
+nodes not reached from node 0 unless given --unreachable prune, which leaves them
+as they are, or --unreachable separate, which restructures them on their own.
+";
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+	Dot,
+	Json,
+	Artifact,
+}
+
+impl Format {
+	// Artifacts can only be read, so graphs read from them are written as DOT
+	const fn to_output(self) -> Self {
+		match self {
+			Self::Artifact => Self::Dot,
+			_ => self,
+		}
+	}
+}
+
+impl FromStr for Format {
+	type Err = String;
+
+	fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+		match name {
+			"dot" => Ok(Self::Dot),
+			"json" => Ok(Self::Json),
+			"artifact" => Ok(Self::Artifact),
+			_ => Err(format!("unknown format `{name}`")),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Lang {
+	Pseudo,
+	Lua,
+	Wat,
+	C,
+	Rust,
+}
+
+impl FromStr for Lang {
+	type Err = String;
+
+	fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+		match name {
+			"pseudo" => Ok(Self::Pseudo),
+			"lua" => Ok(Self::Lua),
+			"wat" => Ok(Self::Wat),
+			"c" => Ok(Self::C),
+			"rust" => Ok(Self::Rust),
+			_ => Err(format!("unknown language `{name}`")),
+		}
+	}
+}
+
+struct Unreachable(Policy);
+
+impl FromStr for Unreachable {
+	type Err = String;
+
+	fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+		match name {
+			"report" => Ok(Self(Policy::Report)),
+			"prune" => Ok(Self(Policy::Prune)),
+			"separate" => Ok(Self(Policy::Separate)),
+			_ => Err(format!("unknown policy `{name}`")),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Check {
+	Panic,
+	Verify,
+	Trace,
+}
+
+impl FromStr for Check {
+	type Err = String;
+
+	fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+		match name {
+			"panic" => Ok(Self::Panic),
+			"verify" => Ok(Self::Verify),
+			"trace" => Ok(Self::Trace),
+			_ => Err(format!("unknown check `{name}`")),
+		}
+	}
+}
+
+impl Lang {
+	const fn comment(self) -> &'static str {
+		match self {
+			Self::Lua => "--",
+			Self::Wat => ";;",
+			Self::Pseudo | Self::C | Self::Rust => "//",
+		}
+	}
+}
+
+// Original nodes are written as calls to functions named after them
+impl Blocks for Lang {
+	fn contents(&mut self, id: usize) -> String {
+		match self {
+			Self::Lua => format!("block_{id}()"),
+			Self::Wat => format!("call $block_{id}"),
+			Self::Pseudo | Self::C | Self::Rust => format!("block_{id}();"),
+		}
+	}
+
+	fn selector(&mut self, id: usize) -> String {
+		match self {
+			Self::Pseudo => format!("select_{id}"),
+			Self::Wat => format!("call $select_{id}"),
+			Self::Lua | Self::C | Self::Rust => format!("select_{id}()"),
+		}
+	}
+}
+
+struct Options {
+	input: Option<String>,
+	flags: BTreeMap<String, Option<String>>,
+}
+
+impl Options {
+	fn parse<I: Iterator<Item = String>>(args: I) -> Result<Self> {
+		let mut input = None;
+		let mut flags = BTreeMap::new();
+		let mut args = args.peekable();
+
+		while let Some(arg) = args.next() {
+			if let Some(name) = arg.strip_prefix("--") {
+				let value = args.next_if(|value| !value.starts_with("--"));
+
+				flags.insert(name.to_string(), value);
+			} else if input.replace(arg).is_some() {
+				return Err("more than one input file given".into());
+			}
+		}
+
+		Ok(Self { input, flags })
+	}
+
+	// Switches take no value, so one read after them is the input
+	fn has(&mut self, name: &str) -> Result<bool> {
+		let Some(value) = self.flags.remove(name) else {
+			return Ok(false);
+		};
+
+		if value.is_some_and(|value| self.input.replace(value).is_some()) {
+			return Err("more than one input file given".into());
+		}
+
+		Ok(true)
+	}
+
+	fn get<T>(&mut self, name: &str) -> Result<Option<T>>
+	where
+		T: FromStr,
+		T::Err: std::fmt::Display,
+	{
+		let Some(value) = self.flags.remove(name) else {
+			return Ok(None);
+		};
+
+		let value = value.ok_or_else(|| format!("`--{name}` needs a value"))?;
+
+		value
+			.parse()
+			.map(Some)
+			.map_err(|error| format!("`--{name}`: {error}").into())
+	}
+
+	// Flags are taken as they are used, so any left over are unknown
+	fn finish(&self) -> Result<()> {
+		match self.flags.keys().next() {
+			Some(name) => Err(format!("unknown option `--{name}`").into()),
+			None => Ok(()),
+		}
+	}
+}
+
+// Returns the graph along with the format it was read as
+fn read_graph(options: &mut Options) -> Result<(List, Format)> {
+	let from = options.get::<Format>("from")?;
+	let mut source = Vec::new();
+
+	match &options.input {
+		Some(path) => source = std::fs::read(path)?,
+		None => _ = std::io::stdin().read_to_end(&mut source)?,
+	}
+
+	// Only JSON graphs start with an object
+	let from = from.unwrap_or(if source.trim_ascii_start().starts_with(b"{") {
+		Format::Json
+	} else {
+		Format::Dot
+	});
+
+	let list = match from {
+		Format::Dot => Reader::new().run(std::str::from_utf8(&source)?)?,
+		Format::Json => serde_json::from_slice(&source)?,
+		Format::Artifact => {
+			let unstructured = arbitrary::Unstructured::new(&source);
+
+			arbitrary::Arbitrary::arbitrary_take_rest(unstructured)?
+		}
+	};
+
+	Ok((list, from))
+}
+
+fn write_graph(list: &List, to: Format) -> Result<()> {
+	let mut out = std::io::stdout().lock();
+
+	match to {
+		Format::Dot => Writer::new().run(list, list.ids().as_slice(), &mut out)?,
+		Format::Json => {
+			serde_json::to_writer_pretty(&mut out, list)?;
+			writeln!(out)?;
+		}
+		Format::Artifact => return Err("graphs cannot be written as artifacts".into()),
+	}
+
+	Ok(())
+}
+
+// The options shared by the commands that restructure
+#[derive(Clone, Copy)]
+struct Passes {
+	options: driver::Options,
+}
+
+impl Passes {
+	fn parse(options: &mut Options) -> Result<Self> {
+		let unify_exits = options.has("unify-exits")?;
+		let unreachable = options.get::<Unreachable>("unreachable")?;
+
+		Ok(Self {
+			options: driver::Options {
+				unify_exits,
+				unreachable: unreachable.map_or(Policy::Report, |Unreachable(policy)| policy),
+			},
+		})
+	}
+
+	// Returns the start of every region restructured, with node 0 first
+	fn restructure<O>(self, list: &mut List, observer: &mut O) -> Result<Vec<usize>>
+	where
+		O: StructureObserver<List>,
+	{
+		let mut set = list.ids();
+		let mut driver = Driver::new();
+
+		driver.run_observed(list, &mut set, 0, self.options, observer)?;
+
+		Ok(driver.starts().to_vec())
+	}
+}
+
+fn run_structure(mut options: Options) -> Result<()> {
+	let passes = Passes::parse(&mut options)?;
+	let to = options.get("to")?;
+	let (mut list, from) = read_graph(&mut options)?;
+
+	options.finish()?;
+
+	passes.restructure(&mut list, &mut ())?;
+	write_graph(&list, to.unwrap_or_else(|| from.to_output()))
+}
+
+fn run_verify(mut options: Options) -> Result<()> {
+	let spirv = options.has("spirv")?;
+	let (list, _) = read_graph(&mut options)?;
+
+	options.finish()?;
+
+	let mut builder = Builder::new();
+	let set = list.ids();
+
+	builder.run(&list, set.as_slice(), 0)?;
+
+	if spirv {
+		Checker::new().run(&list, set.as_slice(), 0, builder.headers())?;
+	}
+
+	println!("structured");
+
+	Ok(())
+}
+
+fn run_stats(mut options: Options) -> Result<()> {
+	let passes = Passes::parse(&mut options)?;
+	let (mut list, _) = read_graph(&mut options)?;
+	let mut statistics = Statistics::new();
+
+	options.finish()?;
+
+	passes.restructure(&mut list, &mut statistics)?;
+
+	print!("{statistics}");
+
+	Ok(())
+}
+
+fn run_emit(mut options: Options) -> Result<()> {
+	let passes = Passes::parse(&mut options)?;
+	let lang = options.get("lang")?.unwrap_or(Lang::Pseudo);
+	let (mut list, _) = read_graph(&mut options)?;
+
+	options.finish()?;
+
+	let starts = passes.restructure(&mut list, &mut ())?;
+	let mut out = String::new();
+	let mut blocks = lang;
+
+	// Regions separated from the live code are written after it, one after another
+	for (index, &start) in starts.iter().enumerate() {
+		let statements = Builder::new().run(&list, list.ids().as_slice(), start)?;
+
+		if index != 0 {
+			let comment = lang.comment();
+
+			write!(out, "\n{comment} region starting at node {start}\n")?;
+		}
+
+		match lang {
+			Lang::Pseudo => pseudo::Emitter::new().run(&statements, &mut blocks, &mut out)?,
+			Lang::Lua => lua::Emitter::new().run(&statements, &mut blocks, &mut out)?,
+			Lang::Wat => wat::Emitter::new().run(&statements, &mut blocks, &mut out)?,
+			Lang::C => c::Emitter::new().run(&statements, &mut blocks, &mut out)?,
+			Lang::Rust => rust::Emitter::new().run(&statements, &mut blocks, &mut out)?,
+		}
+	}
+
+	print!("{out}");
+
+	Ok(())
+}
+
+const TRACE_LIMIT: usize = 256;
+
+// Runs the graph, taking the successors picked by the choices in turn like the `trace`
+// fuzz target, or returns `None` if the interpreter fails
+fn find_trace(list: &List, choices: &[u8]) -> Option<Vec<usize>> {
+	let mut interpreter = Interpreter::new();
+	let mut decision = 0;
+
+	interpreter
+		.run(list, 0, TRACE_LIMIT, |_, len| {
+			let choice = choices[decision % choices.len()];
+
+			decision += 1;
+
+			usize::from(choice) % len
+		})
+		.ok()?;
+
+	Some(interpreter.trace().to_vec())
+}
+
+// Compares the traces of both graphs for a few fixed sets of choices
+fn is_mismatched(list: &List, restructured: &List) -> bool {
+	let mut state = 0x2545_F491_u32;
+
+	(0..8).any(|_| {
+		let choices: Vec<_> = (0..64)
+			.map(|_| {
+				state ^= state << 13;
+				state ^= state >> 17;
+				state ^= state << 5;
+
+				state.to_le_bytes()[0]
+			})
+			.collect();
+
+		find_trace(list, &choices) != find_trace(restructured, &choices)
+	})
+}
+
+// A graph fails `verify` or `trace` only if it restructures without panicking or errors
+fn is_failing(list: &List, check: Check, passes: Passes) -> bool {
+	let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+		let mut restructured = list.clone();
+
+		if passes.restructure(&mut restructured, &mut ()).is_err() {
+			return false;
+		}
+
+		match check {
+			Check::Panic => false,
+			Check::Verify => Builder::new()
+				.run(&restructured, restructured.ids().as_slice(), 0)
+				.is_err(),
+			Check::Trace => is_mismatched(list, &restructured),
+		}
+	}));
+
+	match check {
+		Check::Panic => result.is_err(),
+		Check::Verify | Check::Trace => result.unwrap_or(false),
+	}
+}
+
+fn run_minimize(mut options: Options) -> Result<()> {
+	let passes = Passes::parse(&mut options)?;
+	let check = options.get("check")?.unwrap_or(Check::Panic);
+	let to = options.get("to")?;
+	let (list, from) = read_graph(&mut options)?;
+	let mut minimizer = Minimizer::new();
+
+	options.finish()?;
+
+	// Panics are expected while minimizing, so their messages are hidden
+	let hook = std::panic::take_hook();
+
+	std::panic::set_hook(Box::new(|_| {}));
+
+	// The hook is put back even if the minimizer itself panics
+	let minimized = std::panic::catch_unwind(AssertUnwindSafe(|| {
+		is_failing(&list, check, passes)
+			.then(|| minimizer.run(&list, |list| is_failing(list, check, passes)))
+	}));
+
+	std::panic::set_hook(hook);
+
+	let minimized = minimized.map_err(|payload| {
+		let message = payload
+			.downcast_ref::<&str>()
+			.map(ToString::to_string)
+			.or_else(|| payload.downcast_ref::<String>().cloned())
+			.unwrap_or_default();
+
+		format!("the minimizer panicked: {message}")
+	})?;
+	let list = minimized.ok_or("the graph does not fail the check")?;
+
+	eprintln!(
+		"minimized to {} nodes in {} tries",
+		list.len(),
+		minimizer.tries()
+	);
+
+	write_graph(&list, to.unwrap_or_else(|| from.to_output()))
+}
+
+// Chances are read like other values, but must be between 0 and 1
+fn get_chance(options: &mut Options, name: &str, default: f64) -> Result<f64> {
+	let chance = options.get(name)?.unwrap_or(default);
+
+	if (0.0..=1.0).contains(&chance) {
+		Ok(chance)
+	} else {
+		Err(format!("`--{name}` must be between 0 and 1").into())
+	}
+}
+
+fn run_gen(mut options: Options) -> Result<()> {
+	let defaults = Parameters::default();
+	let to = options.get("to")?.unwrap_or(Format::Dot);
+	let seed = options.get("seed")?.unwrap_or(0);
+	let parameters = Parameters {
+		nodes: options.get("nodes")?.unwrap_or(defaults.nodes),
+		back_edges: get_chance(&mut options, "back-edges", defaults.back_edges)?,
+		max_degree: options.get("max-degree")?.unwrap_or(defaults.max_degree),
+		irreducibility: get_chance(&mut options, "irreducibility", defaults.irreducibility)?,
+		exits: options.get("exits")?.unwrap_or(defaults.exits),
+	};
+
+	if options.input.is_some() {
+		return Err("`gen` does not read a file".into());
+	}
+
+	options.finish()?;
+
+	// Errors name the option to change
+	let list = Generator::new(seed).run(&parameters).map_err(|error| {
+		let name = match error {
+			generator::Error::NoExits | generator::Error::TooManyExits { .. } => "exits",
+			generator::Error::NoDegree => "max-degree",
+		};
+
+		format!("`--{name}`: {error}")
+	})?;
+
+	write_graph(&list, to)
+}
+
+fn run() -> Result<()> {
+	let mut args = std::env::args().skip(1);
+	let command = args.next().ok_or(USAGE)?;
+	let options = Options::parse(args)?;
+
+	match command.as_str() {
+		"structure" => run_structure(options),
+		"verify" => run_verify(options),
+		"stats" => run_stats(options),
+		"emit" => run_emit(options),
+		"minimize" => run_minimize(options),
+		"gen" => run_gen(options),
+		"help" | "--help" | "-h" => {
+			print!("{USAGE}");
+
+			Ok(())
+		}
+		_ => Err(format!("unknown command `{command}`\n\n{USAGE}").into()),
+	}
+}
+
+fn main() -> ExitCode {
+	match run() {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(error) => {
+			eprintln!("error: {error}");
+
+			ExitCode::FAILURE
+		}
+	}
+}
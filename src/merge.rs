@@ -0,0 +1,184 @@
+use crate::{
+	directed::dominator_finder::DominatorFinder,
+	nodes::{Predecessors, Successors},
+	set::{Set, Slice},
+};
+
+/// The construct started by a header, as declared by `OpSelectionMerge` or `OpLoopMerge`.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum Construct {
+	/// A branch, whose arms meet again at the merge block.
+	Selection,
+
+	/// A loop, repeated only from the continue target.
+	Loop { continue_target: usize },
+}
+
+/// A node starting a construct, with the block its construct ends at. The merge block
+/// is missing when no path leaves the construct normally, such as when every arm
+/// repeats or leaves an enclosing loop, or when the header does not dominate the node
+/// its arms meet at.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub struct Header {
+	pub id: usize,
+	pub construct: Construct,
+	pub merge: Option<usize>,
+}
+
+/// The structured control flow rules a table of headers can break.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum Error {
+	/// The header does not dominate its merge block or continue target.
+	NotDominated { header: usize, id: usize },
+
+	/// The block is the merge block of more than one header.
+	SharedMerge(usize),
+
+	/// The loop at the header has no merge block.
+	MissingMerge(usize),
+
+	/// The edge repeats a loop without coming from its continue target, or goes back
+	/// to a node that is not a loop header.
+	BackEdge { from: usize, to: usize },
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NotDominated { header, id } => {
+				write!(f, "header {header} does not dominate node {id}")
+			}
+			Self::SharedMerge(id) => write!(f, "node {id} merges more than one construct"),
+			Self::MissingMerge(id) => write!(f, "loop at node {id} has no merge block"),
+			Self::BackEdge { from, to } => {
+				write!(
+					f,
+					"edge from node {from} to node {to} is not a loop back edge"
+				)
+			}
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// This structure checks a table of headers, such as the one found by
+/// [`crate::tree::Builder`], against the rules for structured control flow in SPIR-V.
+/// Each header must dominate its merge block and continue target, no block may merge
+/// more than one construct, and only the continue target of a loop may repeat it.
+/// Headers, blocks and edges not reached from the start are left out, as SPIR-V allows
+/// them to be unreachable.
+///
+/// The checker only reports broken rules and adds no blocks. A loop built at the latch
+/// of another has no merge block of its own, and a loop exit reached from outside the
+/// loop is not dominated by its header. Those need an empty block added as the merge
+/// to pass.
+#[derive(Default)]
+pub struct Checker {
+	dominator_finder: DominatorFinder,
+
+	reached: Set,
+	merges: Set,
+	continue_targets: Vec<usize>,
+}
+
+impl Checker {
+	/// Creates a new instance of the checker.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			dominator_finder: DominatorFinder::new(),
+
+			reached: Set::new(),
+			merges: Set::new(),
+			continue_targets: Vec::new(),
+		}
+	}
+
+	fn set_continue_target(&mut self, header: usize, continue_target: usize) {
+		if self.continue_targets.len() <= header {
+			self.continue_targets.resize(header + 1, usize::MAX);
+		}
+
+		self.continue_targets[header] = continue_target;
+	}
+
+	fn check_dominated(&self, header: usize, id: usize) -> Result<(), Error> {
+		if !self.reached[id] || self.dominator_finder.dominates(header, id) {
+			Ok(())
+		} else {
+			Err(Error::NotDominated { header, id })
+		}
+	}
+
+	fn check_header(&mut self, header: &Header) -> Result<(), Error> {
+		if !self.reached[header.id] {
+			return Ok(());
+		}
+
+		if let Some(merge) = header.merge {
+			self.check_dominated(header.id, merge)?;
+
+			if self.merges.insert(merge) {
+				return Err(Error::SharedMerge(merge));
+			}
+		}
+
+		if let Construct::Loop { continue_target } = header.construct {
+			self.check_dominated(header.id, continue_target)?;
+
+			if header.merge.is_none() {
+				return Err(Error::MissingMerge(header.id));
+			}
+
+			self.set_continue_target(header.id, continue_target);
+		}
+
+		Ok(())
+	}
+
+	fn check_back_edges<N: Successors>(&self, nodes: &N) -> Result<(), Error> {
+		for &from in self.dominator_finder.ids() {
+			for to in nodes.successors(from) {
+				if !self.reached[to] || !self.dominator_finder.dominates(to, from) {
+					continue;
+				}
+
+				if self.continue_targets.get(to) != Some(&from) {
+					return Err(Error::BackEdge { from, to });
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Checks the headers of the nodes in the set reached from the start.
+	///
+	/// # Errors
+	///
+	/// Returns the first rule found broken.
+	pub fn run<N>(
+		&mut self,
+		nodes: &N,
+		set: Slice,
+		start: usize,
+		headers: &[Header],
+	) -> Result<(), Error>
+	where
+		N: Predecessors + Successors,
+	{
+		self.dominator_finder.run(nodes, set.ones(), start);
+		self.reached.clear();
+		self.reached
+			.extend(self.dominator_finder.ids().iter().copied());
+		self.merges.clear();
+		self.continue_targets.clear();
+
+		for header in headers {
+			self.check_header(header)?;
+		}
+
+		self.check_back_edges(nodes)
+	}
+}
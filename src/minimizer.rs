@@ -0,0 +1,251 @@
+use crate::{
+	list::{Instruction, List},
+	nodes::{Nodes, Successors},
+};
+
+#[derive(Clone, Default)]
+struct Graph {
+	instructions: Vec<Instruction>,
+	successors: Vec<Vec<usize>>,
+}
+
+impl Graph {
+	fn load(&mut self, list: &List) {
+		self.instructions.clear();
+		self.successors.clear();
+
+		for id in 0..list.len() {
+			self.instructions.push(list.instruction(id).clone());
+			self.successors.push(list.successors(id).collect());
+		}
+	}
+
+	fn to_list(&self) -> List {
+		let mut list = List::with_capacity(self.instructions.len());
+
+		for instruction in &self.instructions {
+			list.add_instruction(instruction.clone());
+		}
+
+		for (id, successors) in self.successors.iter().enumerate() {
+			for &successor in successors {
+				list.add_link(id, successor);
+			}
+		}
+
+		list
+	}
+
+	fn edges(&self) -> usize {
+		self.successors.iter().map(Vec::len).sum()
+	}
+
+	// Removes the node, either dropping the links to it or replacing each of them with
+	// links to its successors
+	fn without_node(&self, id: usize, is_bypassed: bool) -> Self {
+		let rename = |successor: usize| successor - usize::from(successor > id);
+		let bypass: Vec<_> = self.successors[id]
+			.iter()
+			.copied()
+			.filter(|&successor| successor != id)
+			.collect();
+
+		let mut graph = Self::default();
+
+		for (index, successors) in self.successors.iter().enumerate() {
+			if index == id {
+				continue;
+			}
+
+			let mut renamed = Vec::with_capacity(successors.len());
+
+			for &successor in successors {
+				if successor != id {
+					renamed.push(rename(successor));
+				} else if is_bypassed {
+					renamed.extend(bypass.iter().copied().map(rename));
+				}
+			}
+
+			graph.instructions.push(self.instructions[index].clone());
+			graph.successors.push(renamed);
+		}
+
+		graph
+	}
+
+	// Removes the nodes that cannot be reached from node `0`
+	fn without_unreachable(&self) -> Self {
+		let mut renames = vec![usize::MAX; self.successors.len()];
+		let mut stack = vec![0];
+
+		renames[0] = 0;
+
+		while let Some(id) = stack.pop() {
+			for &successor in &self.successors[id] {
+				if renames[successor] == usize::MAX {
+					renames[successor] = 0;
+					stack.push(successor);
+				}
+			}
+		}
+
+		let reached = renames.iter_mut().filter(|rename| **rename != usize::MAX);
+
+		for (new, rename) in reached.enumerate() {
+			*rename = new;
+		}
+
+		let mut graph = Self::default();
+
+		for (id, &rename) in renames.iter().enumerate() {
+			if rename == usize::MAX {
+				continue;
+			}
+
+			let successors = self.successors[id].iter().map(|&id| renames[id]);
+
+			graph.instructions.push(self.instructions[id].clone());
+			graph.successors.push(successors.collect());
+		}
+
+		graph
+	}
+
+	// Removes `len` links starting at the `start`th one, counting in node order
+	fn without_edges(&self, start: usize, len: usize) -> Self {
+		let mut graph = self.clone();
+		let mut position = 0;
+
+		for successors in &mut graph.successors {
+			successors.retain(|_| {
+				position += 1;
+
+				!(start..start + len).contains(&(position - 1))
+			});
+		}
+
+		graph
+	}
+}
+
+/// This structure shrinks a graph while a predicate keeps failing on it, to find a
+/// small reproducer for a bug. It greedily tries removing nodes, splicing nodes out
+/// by linking their predecessors to their successors, and removing ever smaller runs
+/// of links, until no single removal keeps the failure.
+///
+/// Node `0` is never removed, so it stays the entry of the graph. Nodes it can no
+/// longer reach are removed along the way, as the restructurers expect every node to
+/// be reachable.
+#[derive(Default)]
+pub struct Minimizer {
+	graph: Graph,
+	tries: usize,
+}
+
+impl Minimizer {
+	/// Creates a new instance of the minimizer.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			graph: Graph {
+				instructions: Vec::new(),
+				successors: Vec::new(),
+			},
+			tries: 0,
+		}
+	}
+
+	/// Returns the number of times the predicate was run by the last minimization.
+	#[must_use]
+	pub const fn tries(&self) -> usize {
+		self.tries
+	}
+
+	fn try_graph<F>(&mut self, graph: Graph, is_failing: &mut F) -> bool
+	where
+		F: FnMut(&List) -> bool,
+	{
+		let graph = graph.without_unreachable();
+
+		self.tries += 1;
+
+		if is_failing(&graph.to_list()) {
+			self.graph = graph;
+
+			true
+		} else {
+			false
+		}
+	}
+
+	fn remove_nodes<F>(&mut self, is_bypassed: bool, is_failing: &mut F) -> bool
+	where
+		F: FnMut(&List) -> bool,
+	{
+		let mut is_changed = false;
+
+		for id in (1..self.graph.instructions.len()).rev() {
+			// Each removal can take unreachable nodes with it, so the graph may shrink
+			// past the nodes left to try
+			if id >= self.graph.instructions.len() {
+				continue;
+			}
+
+			let graph = self.graph.without_node(id, is_bypassed);
+
+			is_changed |= self.try_graph(graph, is_failing);
+		}
+
+		is_changed
+	}
+
+	fn remove_edges<F>(&mut self, is_failing: &mut F) -> bool
+	where
+		F: FnMut(&List) -> bool,
+	{
+		let mut is_changed = false;
+		let mut len = self.graph.edges().div_ceil(2);
+
+		while len != 0 {
+			let mut start = 0;
+
+			while start < self.graph.edges() {
+				let graph = self.graph.without_edges(start, len);
+
+				if self.try_graph(graph, is_failing) {
+					is_changed = true;
+				} else {
+					start += len;
+				}
+			}
+
+			len /= 2;
+		}
+
+		is_changed
+	}
+
+	/// Returns the smallest graph found on which the predicate still returns `true`.
+	/// The predicate should hold for the given graph.
+	pub fn run<F>(&mut self, list: &List, mut is_failing: F) -> List
+	where
+		F: FnMut(&List) -> bool,
+	{
+		self.graph.load(list);
+		self.tries = 0;
+
+		loop {
+			let mut is_changed = self.remove_nodes(false, &mut is_failing);
+
+			is_changed |= self.remove_nodes(true, &mut is_failing);
+			is_changed |= self.remove_edges(&mut is_failing);
+
+			if !is_changed {
+				break;
+			}
+		}
+
+		self.graph.to_list()
+	}
+}
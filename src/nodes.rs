@@ -6,12 +6,49 @@ pub trait Successors {
 	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_;
 }
 
-/// A reserved variable for synthetic control flow nodes.
+/// A reserved variable for synthetic control flow nodes. More may be added as passes
+/// are, so matches outside this crate need a wildcard arm.
+#[non_exhaustive]
 #[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Var {
 	Destination,
 	Repetition,
 	Branch,
+	Exit,
+}
+
+/// The contents of a node added by the structurer.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum Synthetic {
+	NoOperation,
+	Selection(Var),
+	Assignment(Var, usize),
+}
+
+/// How the restructurers may treat a link.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EdgeKind {
+	/// A link that may be redirected through synthetic nodes.
+	#[default]
+	Normal,
+
+	/// A link that is never redirected but still counts when finding loops and
+	/// branches, such as one taken by `longjmp`.
+	Pinned,
+
+	/// A link that is never redirected and is left out when finding loops and
+	/// branches, such as an exception or unwind edge.
+	Ignored,
+}
+
+/// A control flow graph that can tell which nodes were added by the structurer.
+pub trait Synthetics {
+	/// Returns the contents of the node if it was added by the structurer.
+	fn synthetic(&self, id: usize) -> Option<Synthetic>;
 }
 
 /// A control flow graph.
@@ -33,4 +70,51 @@ pub trait Nodes: Predecessors + Successors {
 
 	/// Replaces the link from the `from` node to the `to` node with a link to the `new` node.
 	fn replace_link(&mut self, from: usize, to: usize, new: usize);
+
+	/// Removes one link from the `from` node to the `to` node.
+	fn remove_link(&mut self, from: usize, to: usize);
+
+	/// Returns how the restructurers may treat the links from the `from` node to the `to`
+	/// node. Links are [`EdgeKind::Normal`] unless the graph says otherwise. When
+	/// parallel links differ, the most restrictive kind is returned, with
+	/// [`EdgeKind::Pinned`] before [`EdgeKind::Ignored`].
+	#[allow(unused_variables)]
+	fn edge_kind(&self, from: usize, to: usize) -> EdgeKind {
+		EdgeKind::Normal
+	}
+}
+
+/// Returns the predecessors of the node, without the links the restructurers ignore.
+pub(crate) fn structural_predecessors<N: Nodes>(
+	nodes: &N,
+	id: usize,
+) -> impl Iterator<Item = usize> + '_ {
+	nodes
+		.predecessors(id)
+		.filter(move |&predecessor| nodes.edge_kind(predecessor, id) != EdgeKind::Ignored)
+}
+
+/// Returns the successors of the node, without the links the restructurers ignore.
+pub(crate) fn structural_successors<N: Nodes>(
+	nodes: &N,
+	id: usize,
+) -> impl Iterator<Item = usize> + '_ {
+	nodes
+		.successors(id)
+		.filter(move |&successor| nodes.edge_kind(id, successor) != EdgeKind::Ignored)
+}
+
+/// A graph without the links the restructurers ignore.
+pub(crate) struct Structural<'a, N>(pub &'a N);
+
+impl<N: Nodes> Predecessors for Structural<'_, N> {
+	fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		structural_predecessors(self.0, id)
+	}
+}
+
+impl<N: Nodes> Successors for Structural<'_, N> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		structural_successors(self.0, id)
+	}
 }
@@ -0,0 +1,229 @@
+use crate::{nodes::Nodes, set::Set};
+
+/// What is done with the nodes of a set that cannot be reached from its start.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Default, Debug)]
+pub enum Policy {
+	/// Fails with the unreachable nodes, leaving the graph as it is.
+	#[default]
+	Report,
+
+	/// Cuts the links leaving the unreachable nodes and removes them from the set.
+	Prune,
+
+	/// Splits the unreachable nodes into regions of their own, each with a start, and
+	/// cuts the links between regions so they can be restructured independently. A
+	/// region starting in a cycle is given a no-operation node as its start.
+	Separate,
+}
+
+/// The nodes that cannot be reached from the start, found with [`Policy::Report`].
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+pub struct Error {
+	pub ids: Vec<usize>,
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "unreachable nodes:")?;
+
+		for id in &self.ids {
+			write!(f, " {id}")?;
+		}
+
+		Ok(())
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// This structure finds the nodes of a set that cannot be reached from its start and
+/// handles them according to a [`Policy`]. Such nodes are common in decompiled code,
+/// and the restructurers would otherwise take their links into the live nodes as
+/// entries, changing how the live code is structured.
+///
+/// The links it cuts are returned as pairs of nodes, and no node is removed, so the
+/// identifiers of the nodes never change.
+#[derive(Default)]
+pub struct Pruner {
+	regions: Vec<usize>,
+	starts: Vec<usize>,
+	stack: Vec<usize>,
+	unreachable: Vec<usize>,
+	cut: Vec<(usize, usize)>,
+}
+
+impl Pruner {
+	/// Creates a new instance of the pruner.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			regions: Vec::new(),
+			starts: Vec::new(),
+			stack: Vec::new(),
+			unreachable: Vec::new(),
+			cut: Vec::new(),
+		}
+	}
+
+	/// Returns the nodes that could not be reached from the start, as of the last run.
+	#[must_use]
+	pub fn unreachable(&self) -> &[usize] {
+		&self.unreachable
+	}
+
+	/// Returns the start of every region, as of the last run. The first is the start
+	/// given, and the others are only found with [`Policy::Separate`].
+	#[must_use]
+	pub fn starts(&self) -> &[usize] {
+		&self.starts
+	}
+
+	/// Returns the links cut from the graph during the last run, as pairs of nodes.
+	#[must_use]
+	pub fn cut(&self) -> &[(usize, usize)] {
+		&self.cut
+	}
+
+	// Marks the nodes of the set reached from the start that are not yet in a region
+	fn add_region<N: Nodes>(&mut self, nodes: &N, set: &Set, start: usize) {
+		let region = self.starts.len();
+
+		self.starts.push(start);
+		self.stack.push(start);
+
+		while let Some(id) = self.stack.pop() {
+			if !set[id] || self.regions[id] != usize::MAX {
+				continue;
+			}
+
+			self.regions[id] = region;
+			self.stack.extend(nodes.successors(id));
+		}
+	}
+
+	// Regions start from the nodes without predecessors first, so only nodes in
+	// cycles that nothing leads into start one otherwise
+	fn find_regions<N: Nodes>(&mut self, nodes: &N, set: &Set) {
+		let is_root = |id: usize| !nodes.predecessors(id).any(|id| set[id]);
+		let roots: Vec<_> = self
+			.unreachable
+			.iter()
+			.copied()
+			.filter(|&id| is_root(id))
+			.chain(self.unreachable.iter().copied())
+			.collect();
+
+		for id in roots {
+			if self.regions[id] == usize::MAX {
+				self.add_region(nodes, set, id);
+			}
+		}
+	}
+
+	// Regions starting in a cycle get a new start leading into it, as the restructurers
+	// need every loop to be entered from outside
+	fn add_entries<N: Nodes>(&mut self, nodes: &mut N, set: &mut Set) {
+		for start in self.starts.iter_mut().skip(1) {
+			if nodes.predecessors(*start).any(|id| set[id]) {
+				let entry = nodes.add_no_operation();
+
+				nodes.add_link(entry, *start);
+				set.insert(entry);
+
+				*start = entry;
+			}
+		}
+	}
+
+	fn find_unreachable<N: Nodes>(&mut self, nodes: &N, set: &Set, start: usize) {
+		let len = set.ones().max().map_or(0, |id| id + 1);
+
+		self.regions.clear();
+		self.regions.resize(len, usize::MAX);
+		self.starts.clear();
+		self.unreachable.clear();
+		self.cut.clear();
+
+		self.add_region(nodes, set, start);
+
+		let regions = &self.regions;
+
+		self.unreachable
+			.extend(set.ones().filter(|&id| regions[id] == usize::MAX));
+	}
+
+	// Removes the links leaving the set's nodes that are cut
+	fn cut_links<N, F>(&mut self, nodes: &mut N, set: &Set, mut is_cut: F)
+	where
+		N: Nodes,
+		F: FnMut(usize, usize) -> bool,
+	{
+		for id in set.ones() {
+			self.cut.extend(
+				nodes
+					.successors(id)
+					.filter(|&successor| is_cut(id, successor))
+					.map(|successor| (id, successor)),
+			);
+		}
+
+		for &(from, to) in &self.cut {
+			nodes.remove_link(from, to);
+		}
+	}
+
+	/// Handles the nodes of the set that cannot be reached from the start. Nothing is
+	/// changed when every node is reached.
+	///
+	/// # Errors
+	///
+	/// Returns an error if there are unreachable nodes and the policy is to report them.
+	pub fn run<N: Nodes>(
+		&mut self,
+		nodes: &mut N,
+		set: &mut Set,
+		start: usize,
+		policy: Policy,
+	) -> Result<(), Error> {
+		self.find_unreachable(nodes, set, start);
+
+		if self.unreachable.is_empty() {
+			return Ok(());
+		}
+
+		match policy {
+			Policy::Report => {
+				return Err(Error {
+					ids: self.unreachable.clone(),
+				})
+			}
+			Policy::Prune => {
+				let regions = std::mem::take(&mut self.regions);
+
+				self.cut_links(nodes, set, |from, _| regions[from] == usize::MAX);
+
+				self.regions = regions;
+
+				for &id in &self.unreachable {
+					set.remove(id);
+				}
+			}
+			Policy::Separate => {
+				self.find_regions(nodes, set);
+
+				let regions = std::mem::take(&mut self.regions);
+
+				// Links leaving the set are left to whoever handles the nodes outside it
+				self.cut_links(nodes, set, |from, to| {
+					set[to] && regions[from] != regions[to]
+				});
+
+				self.regions = regions;
+
+				self.add_entries(nodes, set);
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -1,4 +1,8 @@
-use crate::{nodes::Nodes, set::Set};
+use crate::{
+	nodes::{Nodes, Structural, Successors},
+	set::Set,
+	structurer::observer::StructureObserver,
+};
 
 use super::single::{Branch, Single};
 
@@ -10,6 +14,7 @@ pub struct Bulk {
 
 	set: Set,
 	branches: Vec<Branch>,
+	stack: Vec<usize>,
 }
 
 impl Bulk {
@@ -21,16 +26,18 @@ impl Bulk {
 
 			set: Set::new(),
 			branches: Vec::new(),
+			stack: Vec::new(),
 		}
 	}
 
 	fn find_branch_head<N: Nodes>(&mut self, nodes: &N, mut start: usize) -> Option<usize> {
-		loop {
-			if nodes.successors(start).any(|id| !self.set[id]) {
-				return None;
-			}
+		let nodes = Structural(nodes);
 
-			let mut successors = nodes.successors(start).filter(|&id| start != id);
+		loop {
+			// Successors outside the set are reached by repeating a loop
+			let mut successors = nodes
+				.successors(start)
+				.filter(|&id| start != id && self.set[id]);
 			let successor = successors.next()?;
 
 			if successors.next().is_some() {
@@ -43,28 +50,116 @@ impl Bulk {
 		}
 	}
 
-	fn restructure_branch<N: Nodes>(&mut self, nodes: &mut N, head: usize) {
-		if let Some(exit) = self.single.run(nodes, self.set.as_slice(), head) {
+	// A branch left as it is has no exit to start its tail from, so the tail is split
+	// between its continuations by which one reaches each node first
+	fn add_tail_regions<N: Nodes>(&mut self, nodes: &N) {
+		let nodes = Structural(nodes);
+		let mut tail = std::mem::take(self.single.tail_mut());
+
+		for &start in self.single.continuations() {
+			let mut set = Set::new();
+
+			self.stack.push(start);
+
+			while let Some(id) = self.stack.pop() {
+				if tail.remove(id) {
+					set.insert(id);
+
+					self.stack.extend(nodes.successors(id));
+				}
+			}
+
+			if set[start] {
+				self.branches.push(Branch { set, start });
+			}
+		}
+	}
+
+	fn restructure_branch<N, O>(
+		&mut self,
+		nodes: &mut N,
+		head: usize,
+		observer: &mut O,
+		incremental: bool,
+	) where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
+		if !incremental {
+			self.single
+				.find_dominators(nodes, self.set.as_slice(), head);
+		}
+
+		if let Some(exit) =
+			self.single
+				.run_incremental_observed(nodes, self.set.as_slice(), head, observer)
+		{
 			let tail = std::mem::take(self.single.tail_mut());
 
 			self.branches.push(Branch {
 				set: tail,
 				start: exit,
 			});
+
+			// Branches may reach the exit directly, so it is part of their region
+			for Branch { set, .. } in self.single.branches_mut() {
+				set.insert(exit);
+			}
+		} else {
+			self.add_tail_regions(nodes);
 		}
 
 		self.branches.append(self.single.branches_mut());
 	}
 
 	/// Restructures the nodes in the given set.
-	pub fn run<N: Nodes>(&mut self, nodes: &mut N, set: &mut Set, mut start: usize) {
+	pub fn run<N: Nodes>(&mut self, nodes: &mut N, set: &mut Set, start: usize) {
+		self.run_observed(nodes, set, start, &mut ());
+	}
+
+	/// Restructures the nodes in the given set like [`Bulk::run`], but finds the
+	/// dominators of every branch from scratch instead of keeping them up to date.
+	/// It is slower and exists to check the incremental updates against.
+	pub fn run_recomputed<N: Nodes>(&mut self, nodes: &mut N, set: &mut Set, start: usize) {
+		self.run_with(nodes, set, start, &mut (), false);
+	}
+
+	/// Restructures the nodes in the given set like [`Bulk::run`], reporting what it
+	/// does to the observer.
+	pub fn run_observed<N, O>(
+		&mut self,
+		nodes: &mut N,
+		set: &mut Set,
+		start: usize,
+		observer: &mut O,
+	) where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
+		self.run_with(nodes, set, start, observer, true);
+	}
+
+	fn run_with<N, O>(
+		&mut self,
+		nodes: &mut N,
+		set: &mut Set,
+		mut start: usize,
+		observer: &mut O,
+		incremental: bool,
+	) where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
 		self.set.clone_from(set);
+		self.single.find_dominators(nodes, set.as_slice(), start);
 
 		loop {
 			if let Some(head) = self.find_branch_head(nodes, start) {
-				self.restructure_branch(nodes, head);
+				self.restructure_branch(nodes, head, observer, incremental);
 
 				set.extend(self.single.synthetics().iter().copied());
+
+				observer.on_restructured(nodes, set.as_slice());
 			}
 
 			if let Some(branch) = self.branches.pop() {
@@ -2,4 +2,4 @@ mod bulk;
 mod single;
 
 pub use bulk::Bulk;
-pub use single::Single;
+pub use single::{Branch, Single};
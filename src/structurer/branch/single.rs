@@ -1,7 +1,11 @@
 use crate::{
-	directed::dominator_finder::DominatorFinder,
-	nodes::{Nodes, Var},
+	directed::{dominator_finder::DominatorFinder, dominator_tree::DominatorTree},
+	nodes::{EdgeKind, Nodes, Structural, Var},
 	set::{Set, Slice},
+	structurer::{
+		observer::{Observed, StructureObserver},
+		provenance::{Origin, Role},
+	},
 };
 
 pub struct Branch {
@@ -12,6 +16,9 @@ pub struct Branch {
 /// This structure implements a single pass of this algorithm. It assumes that the set
 /// provided is a branch construct and that the start node is the head of that branch.
 /// Additionally, all strongly connected components are assumed to have been normalized.
+///
+/// Links that are not [`EdgeKind::Normal`] are never redirected. A branch with such links
+/// keeps them as they are, so they may still lead into its tail.
 #[derive(Default)]
 pub struct Single {
 	branches: Vec<Branch>,
@@ -19,7 +26,11 @@ pub struct Single {
 	continuations: Vec<usize>,
 
 	synthetics: Vec<usize>,
+	origins: Vec<(usize, Origin)>,
+	kept: Vec<(usize, usize)>,
+	bypassed: Set,
 	dominator_finder: DominatorFinder,
+	dominator_tree: DominatorTree,
 }
 
 impl Single {
@@ -32,7 +43,11 @@ impl Single {
 			continuations: Vec::new(),
 
 			synthetics: Vec::new(),
+			origins: Vec::new(),
+			kept: Vec::new(),
+			bypassed: Set::new(),
 			dominator_finder: DominatorFinder::new(),
+			dominator_tree: DominatorTree::new(),
 		}
 	}
 
@@ -46,7 +61,7 @@ impl Single {
 			let mut predecessors = nodes
 				.predecessors(successor)
 				.filter(|&id| set[id])
-				.filter(|&id| !self.dominator_finder.dominates(successor, id));
+				.filter(|&id| !self.dominator_tree.dominates_within(successor, id, head));
 
 			if predecessors.next().is_some() && predecessors.next().is_none() {
 				self.branches.push(Branch {
@@ -62,7 +77,7 @@ impl Single {
 
 		'dominated: for id in set.ones() {
 			for Branch { set, start } in &mut self.branches {
-				if self.dominator_finder.dominates(*start, id) {
+				if self.dominator_tree.dominates_within(*start, id, head) {
 					set.insert(id);
 
 					continue 'dominated;
@@ -75,14 +90,54 @@ impl Single {
 		self.tail.remove(head);
 	}
 
-	fn has_tail_predicates<N: Nodes>(&self, nodes: &N) -> bool {
+	// Branch assignments reach their selection directly or through a funnel, so a node
+	// whose predecessors are all branch assignments is a funnel or a selection.
+	fn is_dispatch<N: Nodes>(nodes: &N, set: Slice, id: usize) -> bool {
+		let mut predecessors = nodes.predecessors(id).peekable();
+
+		set[id]
+			&& (nodes.has_assignment(id, Var::Branch)
+				|| predecessors.peek().is_some()
+					&& predecessors.all(|id| nodes.has_assignment(id, Var::Branch)))
+	}
+
+	fn has_dispatch_predecessors<N: Nodes>(&self, nodes: &N, set: Slice, head: usize) -> bool {
 		self.continuations.iter().any(|&continuation| {
 			nodes
 				.predecessors(continuation)
-				.any(|id| self.tail[id] && nodes.has_assignment(id, Var::Branch))
+				.any(|id| id != head && Self::is_dispatch(nodes, set, id))
 		})
 	}
 
+	// Finds the tail links that may not be redirected, returning whether restructuring
+	// makes progress. Without branches, it would only replace the head with an exit just
+	// like it unless the head has several links to a tail node. This happens when kept
+	// links leave a cycle behind.
+	fn find_kept<N: Nodes>(&mut self, nodes: &N, set: Slice, head: usize) -> bool {
+		let mut redirects = 0;
+		let mut is_repeated = false;
+
+		for &tail in &self.continuations {
+			let mut from_head = 0;
+
+			for id in nodes
+				.predecessors(tail)
+				.filter(|&id| set[id] && !self.tail[id])
+			{
+				if nodes.edge_kind(id, tail) == EdgeKind::Normal {
+					redirects += 1;
+					from_head += usize::from(id == head);
+				} else {
+					self.kept.push((id, tail));
+				}
+			}
+
+			is_repeated |= from_head > 1;
+		}
+
+		redirects != 0 && (is_repeated || !self.branches.is_empty())
+	}
+
 	fn pull_to_tail(&mut self, id: usize) {
 		if self.tail.insert(id) {
 			return;
@@ -93,21 +148,35 @@ impl Single {
 		}
 	}
 
-	fn trim_continuations<N: Nodes>(&mut self, nodes: &N) {
+	fn pull_assignment_to_tail<N: Nodes>(&mut self, nodes: &N, id: usize) {
+		self.pull_to_tail(id);
+
+		let mut predecessors = nodes.predecessors(id);
+
+		if let (Some(destination), None) = (predecessors.next(), predecessors.next()) {
+			if nodes.has_assignment(destination, Var::Destination) {
+				self.pull_to_tail(destination);
+			}
+		}
+	}
+
+	// Branch assignments of an outer branch must stay next to their selection, or the
+	// assignments added for this branch would overwrite them on the way.
+	fn trim_continuations<N: Nodes>(&mut self, nodes: &N, set: Slice, head: usize) {
 		let continuations = std::mem::take(&mut self.continuations);
 
 		for predecessor in continuations.iter().flat_map(|&id| {
 			nodes
 				.predecessors(id)
-				.filter(|&id| nodes.has_assignment(id, Var::Branch))
+				.filter(|&id| id != head && Self::is_dispatch(nodes, set, id))
 		}) {
-			self.pull_to_tail(predecessor);
+			if nodes.has_assignment(predecessor, Var::Branch) {
+				self.pull_assignment_to_tail(nodes, predecessor);
+			} else {
+				self.pull_to_tail(predecessor);
 
-			let mut predecessors = nodes.predecessors(predecessor);
-
-			if let (Some(destination), None) = (predecessors.next(), predecessors.next()) {
-				if nodes.has_assignment(destination, Var::Destination) {
-					self.pull_to_tail(destination);
+				for id in nodes.predecessors(predecessor).filter(|&id| set[id]) {
+					self.pull_assignment_to_tail(nodes, id);
 				}
 			}
 		}
@@ -127,9 +196,9 @@ impl Single {
 		);
 	}
 
-	fn patch_single_continuation(&mut self, tail: usize) {
+	fn patch_single_continuation(&mut self, head: usize, tail: usize) {
 		for Branch { set, start } in &mut self.branches {
-			if self.dominator_finder.dominates(*start, tail) {
+			if self.dominator_tree.dominates_within(*start, tail, head) {
 				set.insert(tail);
 			}
 		}
@@ -138,12 +207,13 @@ impl Single {
 	fn restructure_full<N: Nodes>(&mut self, nodes: &mut N, items: &mut Set, exit: usize) {
 		let mut continuations = Vec::new();
 
-		// Find all tail connections
+		// Find all tail connections, except those that may not be redirected
 		for &tail in &self.continuations {
 			continuations.extend(
 				nodes
 					.predecessors(tail)
-					.filter_map(|predecessor| items[predecessor].then_some((predecessor, tail))),
+					.filter(|&id| items[id] && nodes.edge_kind(id, tail) == EdgeKind::Normal)
+					.map(|predecessor| (predecessor, tail)),
 			);
 		}
 
@@ -153,11 +223,16 @@ impl Single {
 			1 => exit,
 			_ => {
 				let temp = nodes.add_no_operation();
+				let origin = Origin {
+					role: Role::Funnel,
+					edge: None,
+				};
 
 				nodes.add_link(temp, exit);
 
 				items.insert(temp);
 				self.synthetics.push(temp);
+				self.origins.push((temp, origin));
 
 				temp
 			}
@@ -167,12 +242,17 @@ impl Single {
 		for (predecessor, tail) in continuations {
 			let variable = self.continuations.binary_search(&tail).unwrap();
 			let destination = nodes.add_variable(Var::Branch, variable);
+			let origin = Origin {
+				role: Role::Tail,
+				edge: Some((predecessor, tail)),
+			};
 
 			nodes.replace_link(predecessor, tail, destination);
 			nodes.add_link(destination, funnel);
 
 			items.insert(destination);
 			self.synthetics.push(destination);
+			self.origins.push((destination, origin));
 		}
 	}
 
@@ -190,13 +270,22 @@ impl Single {
 		for (index, &tail) in self.continuations.iter().enumerate() {
 			let redirects = nodes.predecessors(tail).filter(|&id| id == head).count();
 
+			if nodes.edge_kind(head, tail) != EdgeKind::Normal {
+				continue;
+			}
+
 			for _ in 0..redirects {
 				let destination = nodes.add_variable(Var::Branch, index);
+				let origin = Origin {
+					role: Role::Tail,
+					edge: Some((head, tail)),
+				};
 
 				nodes.replace_link(head, tail, destination);
 				nodes.add_link(destination, exit);
 
 				self.synthetics.push(destination);
+				self.origins.push((destination, origin));
 			}
 		}
 	}
@@ -206,6 +295,7 @@ impl Single {
 
 		self.tail.insert(exit);
 		self.synthetics.push(exit);
+		self.origins.push((exit, Origin::selection(Role::Tail)));
 
 		self.restructure_fulls(nodes, exit);
 		self.restructure_empties(nodes, head, exit);
@@ -217,12 +307,74 @@ impl Single {
 		exit
 	}
 
+	// Kept links still enter the tail without passing the exit, so the tail nodes they
+	// reach keep the dominator they had, which is the head or a node above it
+	fn find_bypassed<N: Nodes>(&mut self, nodes: &N, exit: usize) {
+		let mut stack: Vec<_> = self.kept.iter().map(|&(_, tail)| tail).collect();
+
+		self.bypassed.clear();
+
+		while let Some(id) = stack.pop() {
+			if id != exit && self.tail[id] && !self.bypassed.insert(id) {
+				stack.extend(nodes.successors(id));
+			}
+		}
+	}
+
+	fn update_dominators<N: Nodes>(&mut self, nodes: &N, exit: usize) {
+		// Synthetic nodes are created before the nodes they depend on
+		for &id in self.synthetics.iter().rev() {
+			self.dominator_tree.insert(nodes, id);
+		}
+
+		self.find_bypassed(nodes, exit);
+
+		// Tail nodes that were dominated from outside the tail are now dominated by the exit
+		for id in self.tail.ones() {
+			let dominator = self.dominator_tree.dominator(id);
+
+			if id != exit
+				&& !self.bypassed[id]
+				&& dominator.is_some_and(|dominator| !self.tail[dominator])
+			{
+				self.dominator_tree.set_dominator(id, exit);
+			}
+		}
+	}
+
 	/// Returns the synthetic nodes created during the restructuring.
 	#[must_use]
 	pub fn synthetics(&self) -> &[usize] {
 		&self.synthetics
 	}
 
+	/// Returns the edges replaced by synthetic nodes during the restructuring, and the
+	/// selections added for them.
+	#[must_use]
+	pub fn origins(&self) -> &[(usize, Origin)] {
+		&self.origins
+	}
+
+	/// Returns the links left in place during the restructuring, as they may not be
+	/// redirected.
+	#[must_use]
+	pub fn kept(&self) -> &[(usize, usize)] {
+		&self.kept
+	}
+
+	/// Returns the tail nodes reached from the branches, as of the last run.
+	#[must_use]
+	pub fn continuations(&self) -> &[usize] {
+		&self.continuations
+	}
+
+	/// Returns the dominators kept up to date by [`Single::run_incremental`], as of the
+	/// last run.
+	#[must_use]
+	pub fn dominators(&self) -> &DominatorTree {
+		&self.dominator_tree
+	}
+
 	/// Returns the tail set of the restructured branch.
 	pub fn tail_mut(&mut self) -> &mut Set {
 		&mut self.tail
@@ -233,30 +385,94 @@ impl Single {
 		&mut self.branches
 	}
 
+	/// Finds the dominators of the given set of nodes starting at the head. They are kept
+	/// up to date by [`Single::run_incremental`] for all sets within the original one.
+	pub fn find_dominators<N: Nodes>(&mut self, nodes: &N, set: Slice, head: usize) {
+		self.dominator_finder
+			.run(&Structural(nodes), set.ones(), head);
+		self.dominator_tree.load(&self.dominator_finder);
+	}
+
 	/// Applies the restructuring algorithm to the given set of nodes starting at the head.
-	/// The end node of the structured branch is returned, if applicable.
+	/// The end node of the structured branch is returned, if applicable. A branch left as
+	/// it is, as links that may not be redirected leave nothing to restructure, has none,
+	/// but its tail and continuations are still found for the caller to go on with.
 	pub fn run<N: Nodes>(&mut self, nodes: &mut N, set: Slice, head: usize) -> Option<usize> {
-		self.dominator_finder.run(nodes, set.ones(), head);
+		self.find_dominators(nodes, set, head);
+		self.run_incremental(nodes, set, head)
+	}
+
+	/// Applies the restructuring algorithm like [`Single::run`], but reuses the dominators
+	/// from an earlier [`Single::find_dominators`] or [`Single::run_incremental`] call.
+	pub fn run_incremental<N: Nodes>(
+		&mut self,
+		nodes: &mut N,
+		set: Slice,
+		head: usize,
+	) -> Option<usize> {
+		self.run_incremental_observed(nodes, set, head, &mut ())
+	}
+
+	/// Applies the restructuring algorithm like [`Single::run_incremental`], reporting
+	/// what it does to the observer.
+	pub fn run_incremental_observed<N, O>(
+		&mut self,
+		nodes: &mut N,
+		set: Slice,
+		head: usize,
+		observer: &mut O,
+	) -> Option<usize>
+	where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
+		let nodes = &mut Observed::new(nodes, observer);
+
+		self.synthetics.clear();
+		self.origins.clear();
+		self.kept.clear();
 
 		self.find_branches(nodes, set, head);
 		self.find_elements(set, head);
 		self.find_continuations(nodes, set);
 
-		if let &[exit] = self.continuations.as_slice() {
-			self.patch_single_continuation(exit);
+		let is_restructured = self.continuations.len() > 1;
 
-			Some(exit)
-		} else if self.continuations.is_empty() {
-			None
-		} else {
-			if self.has_tail_predicates(nodes) {
-				self.trim_continuations(nodes);
-				self.find_continuations(nodes, set);
-			}
+		if is_restructured && self.has_dispatch_predecessors(nodes, set, head) {
+			self.trim_continuations(nodes, set, head);
+			self.find_continuations(nodes, set);
+		}
+
+		nodes.observe(|observer, nodes| {
+			observer.on_branch_head(nodes, set, head, &self.branches, &self.continuations);
+		});
+
+		if is_restructured && !self.find_kept(nodes, set, head) {
+			nodes.observe(|observer, nodes| observer.on_links_kept(nodes, set, &self.kept));
+
+			return None;
+		}
 
+		if is_restructured {
 			let exit = self.restructure_branches(nodes, head);
 
+			self.update_dominators(nodes, exit);
+
+			for &(id, origin) in &self.origins {
+				nodes.report(id, origin);
+			}
+
+			if !self.kept.is_empty() {
+				nodes.observe(|observer, nodes| observer.on_links_kept(nodes, set, &self.kept));
+			}
+
 			Some(exit)
+		} else if let &[exit] = self.continuations.as_slice() {
+			self.patch_single_continuation(head, exit);
+
+			Some(exit)
+		} else {
+			None
 		}
 	}
 }
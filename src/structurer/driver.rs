@@ -0,0 +1,138 @@
+use crate::{
+	nodes::Nodes,
+	pruner::{Error, Policy, Pruner},
+	set::Set,
+	structurer::{branch, exit, observer::StructureObserver, repeat},
+};
+
+/// The passes run by [`Driver`] around the restructurers.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Default, Debug)]
+pub struct Options {
+	/// Whether the exits are joined into one with [`exit::Unifier`] first. Otherwise,
+	/// each exit is restructured separately.
+	pub unify_exits: bool,
+
+	/// What is done with the nodes that cannot be reached from the start.
+	pub unreachable: Policy,
+}
+
+/// This structure restructures a whole set of nodes, running the [`Pruner`], the
+/// optional [`exit::Unifier`] and then the loop and branch restructurers on every
+/// region found.
+#[derive(Default)]
+pub struct Driver {
+	pruner: Pruner,
+	unifier: exit::Unifier,
+	repeat: repeat::Bulk,
+	branch: branch::Bulk,
+
+	live: Set,
+	region: Set,
+	stack: Vec<usize>,
+}
+
+impl Driver {
+	/// Creates a new instance of the driver.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			pruner: Pruner::new(),
+			unifier: exit::Unifier::new(),
+			repeat: repeat::Bulk::new(),
+			branch: branch::Bulk::new(),
+
+			live: Set::new(),
+			region: Set::new(),
+			stack: Vec::new(),
+		}
+	}
+
+	/// Returns the start of every region restructured, as of the last run. The first is
+	/// the start given.
+	#[must_use]
+	pub fn starts(&self) -> &[usize] {
+		self.pruner.starts()
+	}
+
+	/// Returns the links cut by the [`Pruner`] during the last run, as pairs of nodes.
+	#[must_use]
+	pub fn cut(&self) -> &[(usize, usize)] {
+		self.pruner.cut()
+	}
+
+	// Finds the nodes of the set reached from the start, which are the region of the
+	// start as the links between regions are cut
+	fn find_region<N: Nodes>(&mut self, nodes: &N, set: &Set, start: usize) {
+		self.region.clear();
+		self.stack.push(start);
+
+		while let Some(id) = self.stack.pop() {
+			if set[id] && !self.region.insert(id) {
+				self.stack.extend(nodes.successors(id));
+			}
+		}
+	}
+
+	/// Restructures the set from the start.
+	///
+	/// # Errors
+	///
+	/// Returns an error if there are unreachable nodes and the policy is to report them.
+	pub fn run<N: Nodes>(
+		&mut self,
+		nodes: &mut N,
+		set: &mut Set,
+		start: usize,
+		options: Options,
+	) -> Result<(), Error> {
+		self.run_observed(nodes, set, start, options, &mut ())
+	}
+
+	/// Restructures the set like [`Driver::run`], reporting what it does to the
+	/// observer.
+	///
+	/// # Errors
+	///
+	/// Returns an error if there are unreachable nodes and the policy is to report them.
+	pub fn run_observed<N, O>(
+		&mut self,
+		nodes: &mut N,
+		set: &mut Set,
+		start: usize,
+		options: Options,
+		observer: &mut O,
+	) -> Result<(), Error>
+	where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
+		self.pruner.run(nodes, set, start, options.unreachable)?;
+
+		// Only the live exits are joined, as the others would then reach the live ones
+		if options.unify_exits {
+			self.live.clone_from(set);
+
+			for &id in self.pruner.unreachable() {
+				self.live.remove(id);
+			}
+
+			self.unifier.run_observed(nodes, &mut self.live, observer);
+
+			set.extend(self.unifier.synthetics().iter().copied());
+		}
+
+		self.repeat.run_observed(nodes, set, observer);
+
+		for index in 0..self.pruner.starts().len() {
+			let start = self.pruner.starts()[index];
+
+			self.find_region(nodes, set, start);
+			self.branch
+				.run_observed(nodes, &mut self.region, start, observer);
+
+			set.extend(self.region.ones());
+		}
+
+		Ok(())
+	}
+}
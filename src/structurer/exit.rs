@@ -0,0 +1,108 @@
+use crate::{
+	nodes::{Nodes, Var},
+	set::Set,
+	structurer::{
+		observer::{Observed, StructureObserver},
+		provenance::{Origin, Role},
+	},
+};
+
+/// This structure joins the nodes of a set without successors into a single exit. Each
+/// of them assigns its index to [`Var::Exit`] and goes on to a new no-operation node,
+/// so the exit that was taken is still known once the graph is restructured.
+///
+/// Running it before the restructurers gives them, and any analysis after them, only
+/// one sink to work with. Without it, each exit is restructured separately. Nodes that
+/// can never reach an exit are left as they are.
+#[derive(Default)]
+pub struct Unifier {
+	exits: Vec<usize>,
+	synthetics: Vec<usize>,
+}
+
+impl Unifier {
+	/// Creates a new instance of the unifier.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			exits: Vec::new(),
+			synthetics: Vec::new(),
+		}
+	}
+
+	/// Returns the nodes without successors, as of the last run. Their position is the
+	/// value they assign.
+	#[must_use]
+	pub fn exits(&self) -> &[usize] {
+		&self.exits
+	}
+
+	/// Returns the synthetic nodes created during the last run.
+	#[must_use]
+	pub fn synthetics(&self) -> &[usize] {
+		&self.synthetics
+	}
+
+	/// Joins the exits of the set, adding the new nodes to it. The single exit of the
+	/// set is returned, which is only new if there were several, or `None` if there are
+	/// none.
+	pub fn run<N: Nodes>(&mut self, nodes: &mut N, set: &mut Set) -> Option<usize> {
+		self.run_observed(nodes, set, &mut ())
+	}
+
+	/// Joins the exits of the set like [`Unifier::run`], reporting what it does to the
+	/// observer.
+	pub fn run_observed<N, O>(
+		&mut self,
+		nodes: &mut N,
+		set: &mut Set,
+		observer: &mut O,
+	) -> Option<usize>
+	where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
+		self.exits.clear();
+		self.synthetics.clear();
+
+		self.exits.extend(
+			set.ones()
+				.filter(|&id| nodes.successors(id).next().is_none()),
+		);
+
+		if let &[] | &[_] = self.exits.as_slice() {
+			return self.exits.first().copied();
+		}
+
+		let origin = Origin {
+			role: Role::Return,
+			edge: None,
+		};
+
+		let observed = &mut Observed::new(nodes, observer);
+		let unified = observed.add_unified_exit();
+
+		// Exit
+		// Exit -> Exit Assignment -> Unified
+		for (index, &exit) in self.exits.iter().enumerate() {
+			let assignment = observed.add_variable(Var::Exit, index);
+
+			observed.add_link(exit, assignment);
+			observed.add_link(assignment, unified);
+
+			self.synthetics.push(assignment);
+		}
+
+		self.synthetics.push(unified);
+
+		for &id in &self.synthetics {
+			observed.report(id, origin);
+		}
+
+		set.extend(self.synthetics.iter().copied());
+
+		observer.on_restructured(nodes, set.as_slice());
+
+		Some(unified)
+	}
+}
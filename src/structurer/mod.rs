@@ -3,4 +3,10 @@
 //     by Helge Bahmann, Google Zurich, Nico Reissmann, Magnus Jahre, and Jan Christian Meyer
 
 pub mod branch;
+pub mod driver;
+pub mod exit;
+pub mod observer;
+pub mod provenance;
 pub mod repeat;
+pub mod statistics;
+pub mod trace;
@@ -0,0 +1,163 @@
+use crate::{
+	nodes::{
+		structural_predecessors, structural_successors, EdgeKind, Nodes, Predecessors, Successors,
+		Synthetic, Var,
+	},
+	set::Slice,
+};
+
+use super::{branch::Branch, provenance::Origin};
+
+/// Callbacks for the events of restructuring. Every method does nothing by default,
+/// so implementations only need the ones they care about.
+#[allow(unused_variables)]
+pub trait StructureObserver<N: ?Sized> {
+	/// Called when a strongly connected component is about to be restructured.
+	fn on_loop_found(&mut self, nodes: &N, set: Slice, entries: &[usize], exits: &[usize]) {}
+
+	/// Called when the repetition selection of a loop is created.
+	fn on_latch_created(&mut self, nodes: &N, latch: usize) {}
+
+	/// Called when a branch head is about to be restructured, with the branches leaving
+	/// it and the tail nodes they continue to.
+	fn on_branch_head(
+		&mut self,
+		nodes: &N,
+		set: Slice,
+		head: usize,
+		branches: &[Branch],
+		continuations: &[usize],
+	) {
+	}
+
+	/// Called when a no-operation node is created to join the tail connections of a branch.
+	fn on_funnel_created(&mut self, nodes: &N, funnel: usize) {}
+
+	/// Called when a no-operation node is created to join the exits of a graph.
+	fn on_exit_created(&mut self, nodes: &N, exit: usize) {}
+
+	/// Called for every synthetic node as soon as it is added to the graph.
+	fn on_synthetic(&mut self, nodes: &N, id: usize, synthetic: Synthetic) {}
+
+	/// Called for every synthetic node with an origin once its loop or branch is done.
+	fn on_origin(&mut self, nodes: &N, id: usize, origin: Origin) {}
+
+	/// Called when links that may not be redirected are left in place, so the loop or
+	/// branch in the set is only partly structured, or not at all. The links may be
+	/// empty if it is blocked by links kept elsewhere, such as a cycle left behind.
+	fn on_links_kept(&mut self, nodes: &N, set: Slice, links: &[(usize, usize)]) {}
+
+	/// Called by the bulk restructurers after each loop or branch is done, with all of
+	/// the nodes they are working on.
+	fn on_restructured(&mut self, nodes: &N, set: Slice) {}
+}
+
+impl<N: ?Sized> StructureObserver<N> for () {}
+
+/// A graph that reports the synthetic nodes added to it and hides the links the
+/// restructurers ignore. The restructurers only add repetition selections as latches
+/// and no-operation nodes as funnels, except for the one joining the exits.
+pub(crate) struct Observed<'a, N, O> {
+	nodes: &'a mut N,
+	observer: &'a mut O,
+}
+
+impl<'a, N, O> Observed<'a, N, O>
+where
+	N: Nodes,
+	O: StructureObserver<N>,
+{
+	pub fn new(nodes: &'a mut N, observer: &'a mut O) -> Self {
+		Self { nodes, observer }
+	}
+
+	pub fn report(&mut self, id: usize, origin: Origin) {
+		self.observer.on_origin(self.nodes, id, origin);
+	}
+
+	// Adds the no-operation node joining the exits, which is not a funnel
+	pub fn add_unified_exit(&mut self) -> usize {
+		let id = self.nodes.add_no_operation();
+
+		self.observer
+			.on_synthetic(self.nodes, id, Synthetic::NoOperation);
+		self.observer.on_exit_created(self.nodes, id);
+
+		id
+	}
+
+	// Passes the graph to the observer, for events about more than one node
+	pub fn observe<F: FnOnce(&mut O, &N)>(&mut self, event: F) {
+		event(self.observer, self.nodes);
+	}
+
+	fn notify(&mut self, id: usize, synthetic: Synthetic) -> usize {
+		self.observer.on_synthetic(self.nodes, id, synthetic);
+
+		match synthetic {
+			Synthetic::NoOperation => self.observer.on_funnel_created(self.nodes, id),
+			Synthetic::Selection(Var::Repetition) => {
+				self.observer.on_latch_created(self.nodes, id);
+			}
+			Synthetic::Selection(_) | Synthetic::Assignment(..) => {}
+		}
+
+		id
+	}
+}
+
+impl<N: Nodes, O> Predecessors for Observed<'_, N, O> {
+	fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		structural_predecessors(self.nodes, id)
+	}
+}
+
+impl<N: Nodes, O> Successors for Observed<'_, N, O> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		structural_successors(self.nodes, id)
+	}
+}
+
+impl<N, O> Nodes for Observed<'_, N, O>
+where
+	N: Nodes,
+	O: StructureObserver<N>,
+{
+	fn has_assignment(&self, id: usize, var: Var) -> bool {
+		self.nodes.has_assignment(id, var)
+	}
+
+	fn add_no_operation(&mut self) -> usize {
+		let id = self.nodes.add_no_operation();
+
+		self.notify(id, Synthetic::NoOperation)
+	}
+
+	fn add_selection(&mut self, var: Var) -> usize {
+		let id = self.nodes.add_selection(var);
+
+		self.notify(id, Synthetic::Selection(var))
+	}
+
+	fn add_variable(&mut self, var: Var, value: usize) -> usize {
+		let id = self.nodes.add_variable(var, value);
+
+		self.notify(id, Synthetic::Assignment(var, value))
+	}
+
+	fn add_link(&mut self, from: usize, to: usize) {
+		self.nodes.add_link(from, to);
+	}
+
+	fn replace_link(&mut self, from: usize, to: usize, new: usize) {
+		self.nodes.replace_link(from, to, new);
+	}
+
+	fn remove_link(&mut self, from: usize, to: usize) {
+		self.nodes.remove_link(from, to);
+	}
+
+	fn edge_kind(&self, from: usize, to: usize) -> EdgeKind {
+		self.nodes.edge_kind(from, to)
+	}
+}
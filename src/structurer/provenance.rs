@@ -0,0 +1,102 @@
+use super::observer::StructureObserver;
+
+/// The reason a synthetic node was added.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Role {
+	/// Selects which entry of a loop is taken, on an edge coming into the loop.
+	Entry,
+
+	/// Leaves a loop, on an edge going out of the loop.
+	Exit,
+
+	/// Repeats a loop, on an edge going back to one of its entries.
+	Continue,
+
+	/// Selects which tail node a branch continues to, on an edge leaving the branch.
+	Tail,
+
+	/// Joins the tail connections of a branch, so it stands in for no single edge.
+	Funnel,
+
+	/// Joins the exits of a graph, or tells which one was taken on the way there, so
+	/// it stands in for no edge.
+	Return,
+}
+
+/// The edge a synthetic node stands in for and why it was added. The selection added
+/// for a role stands in for no single edge, as it picks between all of them.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Origin {
+	pub role: Role,
+	pub edge: Option<(usize, usize)>,
+}
+
+impl Origin {
+	pub(crate) const fn selection(role: Role) -> Self {
+		Self { role, edge: None }
+	}
+}
+
+/// A table from synthetic nodes to their [`Origin`]. It is filled by passing it as the
+/// observer to `run_observed`. Edges are resolved through the origins already in the
+/// table, so an end made by an earlier step is replaced by the end of the edge it stands
+/// in for. Only ends that stand in for no single edge, such as selections, are kept.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Provenance {
+	origins: Vec<Option<Origin>>,
+}
+
+impl Provenance {
+	/// Creates a new, empty table.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			origins: Vec::new(),
+		}
+	}
+
+	/// Returns the origin of the synthetic node, if it has one.
+	#[must_use]
+	pub fn origin(&self, id: usize) -> Option<Origin> {
+		self.origins.get(id).copied().flatten()
+	}
+
+	fn edge(&self, id: usize) -> Option<(usize, usize)> {
+		self.origin(id).and_then(|origin| origin.edge)
+	}
+
+	/// Returns the synthetic nodes with an origin, in order.
+	pub fn iter(&self) -> impl Iterator<Item = (usize, Origin)> + '_ {
+		self.origins
+			.iter()
+			.enumerate()
+			.filter_map(|(id, origin)| origin.map(|origin| (id, origin)))
+	}
+
+	/// Removes all recorded origins.
+	pub fn clear(&mut self) {
+		self.origins.clear();
+	}
+}
+
+impl<N: ?Sized> StructureObserver<N> for Provenance {
+	fn on_origin(&mut self, _: &N, id: usize, mut origin: Origin) {
+		if let Some((from, to)) = origin.edge {
+			let from = self.edge(from).map_or(from, |(from, _)| from);
+			let to = self.edge(to).map_or(to, |(_, to)| to);
+
+			origin.edge = Some((from, to));
+		}
+
+		if self.origins.len() <= id {
+			self.origins.resize(id + 1, None);
+		}
+
+		self.origins[id] = Some(origin);
+	}
+}
@@ -1,4 +1,9 @@
-use crate::{directed::strongly_connected_finder::StronglyConnectedFinder, nodes::Nodes, set::Set};
+use crate::{
+	directed::strongly_connected_finder::StronglyConnectedFinder,
+	nodes::{Nodes, Predecessors, Structural, Successors},
+	set::Set,
+	structurer::observer::StructureObserver,
+};
 
 use super::single::Single;
 
@@ -11,6 +16,7 @@ pub struct Bulk {
 
 	set: Set,
 	components: Vec<Set>,
+	hierarchy: Vec<Set>,
 }
 
 impl Bulk {
@@ -23,31 +29,82 @@ impl Bulk {
 
 			set: Set::new(),
 			components: Vec::new(),
+			hierarchy: Vec::new(),
 		}
 	}
 
-	fn find_next_component<N: Nodes>(&mut self, nodes: &N) -> Option<Set> {
+	fn find_nested_components<N>(&mut self, nodes: &N, component: &Set)
+	where
+		N: Predecessors + Successors,
+	{
+		let mut has_entries = false;
+
+		self.set.clone_from(component);
+
+		for id in component.ones() {
+			if nodes.predecessors(id).any(|id| !component[id]) {
+				self.set.remove(id);
+
+				has_entries = true;
+			}
+		}
+
+		// A component without entries is left as is, so one of its nodes stands in for
+		// them to keep it from being found again
+		if !has_entries {
+			let id = component.ones().next().unwrap();
+
+			self.set.remove(id);
+		}
+
 		let set = self.set.as_slice();
 
 		self.strongly_connected_finder.run(nodes, set, |component| {
 			self.components.push(component);
 		});
+	}
 
-		self.components.pop()
+	// Structuring a component only changes edges that leave it or enter one of its
+	// entries, so its nested components are found by cutting the entries off. This
+	// means the whole hierarchy can be found up front, in the order it is structured.
+	fn find_hierarchy<N: Nodes>(&mut self, nodes: &N, set: &Set) {
+		let nodes = &Structural(nodes);
+
+		self.hierarchy.clear();
+
+		self.strongly_connected_finder
+			.run(nodes, set.as_slice(), |component| {
+				self.components.push(component);
+			});
+
+		while let Some(component) = self.components.pop() {
+			self.find_nested_components(nodes, &component);
+
+			self.hierarchy.push(component);
+		}
 	}
 
 	/// Restructures the nodes in the given set.
 	pub fn run<N: Nodes>(&mut self, nodes: &mut N, set: &mut Set) {
-		self.set.clone_from(set);
-
-		while let Some(component) = self.find_next_component(nodes) {
-			self.set.clone_from(&component);
+		self.run_observed(nodes, set, &mut ());
+	}
 
-			let start = self.single.run(nodes, self.set.as_slice());
+	/// Restructures the nodes in the given set like [`Bulk::run`], reporting what it
+	/// does to the observer.
+	pub fn run_observed<N, O>(&mut self, nodes: &mut N, set: &mut Set, observer: &mut O)
+	where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
+		self.find_hierarchy(nodes, set);
 
-			self.set.remove(start);
+		for component in self.hierarchy.drain(..) {
+			self.single
+				.run_observed(nodes, component.as_slice(), observer);
 
 			set.extend(self.single.synthetics().iter().copied());
+
+			observer.on_restructured(nodes, set.as_slice());
 		}
 	}
 }
@@ -1,17 +1,26 @@
 use crate::{
-	nodes::{Nodes, Var},
+	nodes::{EdgeKind, Nodes, Var},
 	set::Slice,
+	structurer::{
+		observer::{Observed, StructureObserver},
+		provenance::{Origin, Role},
+	},
 };
 
 /// This structure implements a single pass of this algorithm. It assumes that the set
 /// provided is a strongly connected component and that there is at least one edge
 /// from outside the set coming in.
+///
+/// Links that are not [`EdgeKind::Normal`] are never redirected. A loop with such links
+/// keeps them as they are and may be left with more than one entry or exit.
 #[derive(Default)]
 pub struct Single {
 	point_in: Vec<usize>,
 	point_out: Vec<usize>,
 
 	synthetics: Vec<usize>,
+	origins: Vec<(usize, Origin)>,
+	kept: Vec<(usize, usize)>,
 }
 
 impl Single {
@@ -23,6 +32,8 @@ impl Single {
 			point_out: Vec::new(),
 
 			synthetics: Vec::new(),
+			origins: Vec::new(),
+			kept: Vec::new(),
 		}
 	}
 
@@ -59,6 +70,34 @@ impl Single {
 		None
 	}
 
+	// Finds the links that may not be redirected, returning whether any link is left to
+	// reach the latch. Entries are only redirected if there are several of them.
+	fn find_kept<N: Nodes>(&mut self, nodes: &N, set: Slice) -> bool {
+		let mut is_latched = false;
+
+		for &entry in &self.point_in {
+			for id in nodes.predecessors(entry) {
+				if nodes.edge_kind(id, entry) == EdgeKind::Normal {
+					is_latched |= set[id];
+				} else if set[id] || self.point_in.len() > 1 {
+					self.kept.push((id, entry));
+				}
+			}
+		}
+
+		for &exit in &self.point_out {
+			for id in nodes.successors(exit).filter(|&id| !set[id]) {
+				if nodes.edge_kind(exit, id) == EdgeKind::Normal {
+					is_latched = true;
+				} else {
+					self.kept.push((exit, id));
+				}
+			}
+		}
+
+		is_latched
+	}
+
 	fn restructure_continues<N: Nodes>(&mut self, nodes: &mut N, set: Slice, latch: usize) {
 		// Predecessor -> Entry
 		// Predecessor -> Destination -> Repetition -> Latch -> Selection -> Entry
@@ -66,8 +105,16 @@ impl Single {
 			let predecessors: Vec<_> = nodes.predecessors(entry).filter(|&id| set[id]).collect();
 
 			for predecessor in predecessors {
+				if nodes.edge_kind(predecessor, entry) != EdgeKind::Normal {
+					continue;
+				}
+
 				let destination = nodes.add_variable(Var::Destination, index);
 				let repetition = nodes.add_variable(Var::Repetition, 1);
+				let origin = Origin {
+					role: Role::Continue,
+					edge: Some((predecessor, entry)),
+				};
 
 				nodes.replace_link(predecessor, entry, destination);
 				nodes.add_link(destination, repetition);
@@ -75,6 +122,8 @@ impl Single {
 
 				self.synthetics.push(destination);
 				self.synthetics.push(repetition);
+				self.origins.push((destination, origin));
+				self.origins.push((repetition, origin));
 			}
 		}
 	}
@@ -83,6 +132,8 @@ impl Single {
 		let selection = nodes.add_selection(Var::Destination);
 
 		self.synthetics.push(selection);
+		self.origins
+			.push((selection, Origin::selection(Role::Entry)));
 
 		// Predecessor -> Entry
 		// Predecessor -> Destination -> Selection -> Entry
@@ -90,12 +141,21 @@ impl Single {
 			let predecessors: Vec<_> = nodes.predecessors(entry).filter(|&id| !set[id]).collect();
 
 			for predecessor in predecessors {
+				if nodes.edge_kind(predecessor, entry) != EdgeKind::Normal {
+					continue;
+				}
+
 				let destination = nodes.add_variable(Var::Destination, index);
+				let origin = Origin {
+					role: Role::Entry,
+					edge: Some((predecessor, entry)),
+				};
 
 				nodes.replace_link(predecessor, entry, destination);
 				nodes.add_link(destination, selection);
 
 				self.synthetics.push(destination);
+				self.origins.push((destination, origin));
 			}
 
 			nodes.add_link(selection, entry);
@@ -108,15 +168,29 @@ impl Single {
 		let selection = nodes.add_selection(Var::Destination);
 
 		self.synthetics.push(selection);
+		self.origins
+			.push((selection, Origin::selection(Role::Exit)));
 
 		// Exit -> Successor
 		// Exit -> Destination -> Repetition -> Latch -> Selection -> Successor
-		for (index, &exit) in self.point_out.iter().enumerate() {
+		//
+		// Each edge gets its own value, as an exit may leave to several successors
+		let mut index = 0;
+
+		for &exit in &self.point_out {
 			let successors: Vec<_> = nodes.successors(exit).filter(|&id| !set[id]).collect();
 
 			for successor in successors {
+				if nodes.edge_kind(exit, successor) != EdgeKind::Normal {
+					continue;
+				}
+
 				let destination = nodes.add_variable(Var::Destination, index);
 				let repetition = nodes.add_variable(Var::Repetition, 0);
+				let origin = Origin {
+					role: Role::Exit,
+					edge: Some((exit, successor)),
+				};
 
 				nodes.replace_link(exit, successor, destination);
 				nodes.add_link(selection, successor);
@@ -126,31 +200,91 @@ impl Single {
 
 				self.synthetics.push(destination);
 				self.synthetics.push(repetition);
+				self.origins.push((destination, origin));
+				self.origins.push((repetition, origin));
+
+				index += 1;
 			}
 		}
 
 		selection
 	}
 
+	/// Returns the nodes entered from outside the set, as of the last run.
+	#[must_use]
+	pub fn entries(&self) -> &[usize] {
+		&self.point_in
+	}
+
+	/// Returns the nodes leaving the set, as of the last run.
+	#[must_use]
+	pub fn exits(&self) -> &[usize] {
+		&self.point_out
+	}
+
+	/// Returns the edges replaced by synthetic nodes during the restructuring, and the
+	/// selections added for them.
+	#[must_use]
+	pub fn origins(&self) -> &[(usize, Origin)] {
+		&self.origins
+	}
+
 	/// Returns the synthetic nodes created during the restructuring.
 	#[must_use]
 	pub fn synthetics(&self) -> &[usize] {
 		&self.synthetics
 	}
 
+	/// Returns the links left in place during the restructuring, as they may not be
+	/// redirected.
+	#[must_use]
+	pub fn kept(&self) -> &[(usize, usize)] {
+		&self.kept
+	}
+
 	/// Applies the restructuring algorithm to the given set of nodes.
-	/// The start node of the structured repetition is returned.
+	/// The start node of the structured repetition is returned. If links that may not be
+	/// redirected leave nothing to restructure, its first entry or node is returned.
 	pub fn run<N: Nodes>(&mut self, nodes: &mut N, set: Slice) -> usize {
-		if let Some(start) = self.find_start_if_structured(nodes, set) {
-			self.synthetics.clear();
+		self.run_observed(nodes, set, &mut ())
+	}
+
+	/// Applies the restructuring algorithm like [`Single::run`], reporting what it does
+	/// to the observer.
+	pub fn run_observed<N, O>(&mut self, nodes: &mut N, set: Slice, observer: &mut O) -> usize
+	where
+		N: Nodes,
+		O: StructureObserver<N>,
+	{
+		let nodes = &mut Observed::new(nodes, observer);
+		let structured = self.find_start_if_structured(nodes, set);
+
+		nodes.observe(|observer, nodes| {
+			observer.on_loop_found(nodes, set, &self.point_in, &self.point_out);
+		});
 
+		self.synthetics.clear();
+		self.origins.clear();
+		self.kept.clear();
+
+		if let Some(start) = structured {
 			return start;
 		}
 
+		// A loop only entered through ignored links has no entries to restructure
+		if self.point_in.is_empty() || !self.find_kept(nodes, set) {
+			nodes.observe(|observer, nodes| observer.on_links_kept(nodes, set, &self.kept));
+
+			let start = self.point_in.first().copied();
+
+			return start.unwrap_or_else(|| set.ones().next().unwrap());
+		}
+
 		let latch = nodes.add_selection(Var::Repetition);
 
-		self.synthetics.clear();
 		self.synthetics.push(latch);
+		self.origins
+			.push((latch, Origin::selection(Role::Continue)));
 
 		let start = if let &[start] = self.point_in.as_slice() {
 			start
@@ -165,6 +299,14 @@ impl Single {
 		nodes.add_link(latch, start);
 		nodes.add_link(latch, end);
 
+		for &(id, origin) in &self.origins {
+			nodes.report(id, origin);
+		}
+
+		if !self.kept.is_empty() {
+			nodes.observe(|observer, nodes| observer.on_links_kept(nodes, set, &self.kept));
+		}
+
 		start
 	}
 }
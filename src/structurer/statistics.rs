@@ -0,0 +1,146 @@
+use crate::{
+	nodes::{Synthetic, Var},
+	set::Slice,
+};
+
+use super::{branch::Branch, observer::StructureObserver};
+
+/// Counts of what the restructurers found and added. It is filled by passing it as the
+/// observer to `run_observed`, and adds to any counts already present.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+	/// The number of strongly connected components visited.
+	pub loops: usize,
+
+	/// The number of loops with more than one entry.
+	pub irreducible_loops: usize,
+
+	/// The number of nodes in the largest loop.
+	pub largest_loop: usize,
+
+	/// The number of branch heads visited.
+	pub branch_heads: usize,
+
+	/// The number of no-operation nodes added to join branches.
+	pub funnels: usize,
+
+	/// The number of no-operation nodes added to join the exits of a graph, which are
+	/// not counted as funnels.
+	pub unified_exits: usize,
+
+	/// The number of selections added, for any variable.
+	pub selections: usize,
+
+	/// The number of loops and branches only partly structured, as they kept links
+	/// that may not be redirected.
+	pub partial_regions: usize,
+
+	pub destination_assignments: usize,
+	pub repetition_assignments: usize,
+	pub branch_assignments: usize,
+	pub exit_assignments: usize,
+}
+
+impl Statistics {
+	/// Creates a new instance with all counts at zero.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			loops: 0,
+			irreducible_loops: 0,
+			largest_loop: 0,
+			branch_heads: 0,
+			funnels: 0,
+			unified_exits: 0,
+			selections: 0,
+			partial_regions: 0,
+			destination_assignments: 0,
+			repetition_assignments: 0,
+			branch_assignments: 0,
+			exit_assignments: 0,
+		}
+	}
+
+	/// Returns the number of assignments added to the variable.
+	#[must_use]
+	pub const fn assignments(&self, var: Var) -> usize {
+		match var {
+			Var::Destination => self.destination_assignments,
+			Var::Repetition => self.repetition_assignments,
+			Var::Branch => self.branch_assignments,
+			Var::Exit => self.exit_assignments,
+		}
+	}
+
+	/// Returns the number of synthetic nodes added.
+	#[must_use]
+	pub const fn synthetics(&self) -> usize {
+		self.funnels
+			+ self.unified_exits
+			+ self.selections
+			+ self.destination_assignments
+			+ self.repetition_assignments
+			+ self.branch_assignments
+			+ self.exit_assignments
+	}
+}
+
+impl std::fmt::Display for Statistics {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "loops: {}", self.loops)?;
+		writeln!(f, "irreducible loops: {}", self.irreducible_loops)?;
+		writeln!(f, "largest loop: {}", self.largest_loop)?;
+		writeln!(f, "branch heads: {}", self.branch_heads)?;
+		writeln!(f, "funnels: {}", self.funnels)?;
+		writeln!(f, "unified exits: {}", self.unified_exits)?;
+		writeln!(f, "selections: {}", self.selections)?;
+		writeln!(f, "partial regions: {}", self.partial_regions)?;
+		writeln!(
+			f,
+			"destination assignments: {}",
+			self.destination_assignments
+		)?;
+		writeln!(f, "repetition assignments: {}", self.repetition_assignments)?;
+		writeln!(f, "branch assignments: {}", self.branch_assignments)?;
+		writeln!(f, "exit assignments: {}", self.exit_assignments)
+	}
+}
+
+impl<N: ?Sized> StructureObserver<N> for Statistics {
+	fn on_loop_found(&mut self, _: &N, set: Slice, entries: &[usize], _: &[usize]) {
+		self.loops += 1;
+		self.largest_loop = self.largest_loop.max(set.ones().count());
+
+		if entries.len() > 1 {
+			self.irreducible_loops += 1;
+		}
+	}
+
+	fn on_branch_head(&mut self, _: &N, _: Slice, _: usize, _: &[Branch], _: &[usize]) {
+		self.branch_heads += 1;
+	}
+
+	fn on_funnel_created(&mut self, _: &N, _: usize) {
+		self.funnels += 1;
+	}
+
+	fn on_exit_created(&mut self, _: &N, _: usize) {
+		self.unified_exits += 1;
+	}
+
+	fn on_links_kept(&mut self, _: &N, _: Slice, _: &[(usize, usize)]) {
+		self.partial_regions += 1;
+	}
+
+	fn on_synthetic(&mut self, _: &N, _: usize, synthetic: Synthetic) {
+		match synthetic {
+			Synthetic::NoOperation => {}
+			Synthetic::Selection(_) => self.selections += 1,
+			Synthetic::Assignment(Var::Destination, _) => self.destination_assignments += 1,
+			Synthetic::Assignment(Var::Repetition, _) => self.repetition_assignments += 1,
+			Synthetic::Assignment(Var::Branch, _) => self.branch_assignments += 1,
+			Synthetic::Assignment(Var::Exit, _) => self.exit_assignments += 1,
+		}
+	}
+}
@@ -0,0 +1,218 @@
+use std::{
+	fs::File,
+	io::{Result, Write},
+	path::Path,
+};
+
+use crate::{
+	dot::{Region, RegionKind, Writer},
+	nodes::{Nodes, Synthetic, Synthetics},
+	set::{Set, Slice},
+};
+
+use super::{branch::Branch, observer::StructureObserver};
+
+/// A single pass of a restructurer over a set of nodes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum Step {
+	/// A strongly connected component given to [`repeat::Single`](super::repeat::Single).
+	Loop {
+		#[cfg_attr(feature = "serde", serde(serialize_with = "serialize_set"))]
+		set: Set,
+		entries: Vec<usize>,
+		exits: Vec<usize>,
+		synthetics: Vec<usize>,
+	},
+
+	/// A branch construct given to [`branch::Single`](super::branch::Single).
+	Branch {
+		#[cfg_attr(feature = "serde", serde(serialize_with = "serialize_set"))]
+		set: Set,
+		head: usize,
+		continuations: Vec<usize>,
+		synthetics: Vec<usize>,
+	},
+}
+
+impl Step {
+	fn to_region(&self) -> Region {
+		let (kind, set, start, synthetics) = match self {
+			Self::Loop {
+				set,
+				entries,
+				synthetics,
+				..
+			} => {
+				let start = entries.first().copied().or_else(|| set.ones().next());
+
+				(RegionKind::Loop, set, start.unwrap_or_default(), synthetics)
+			}
+			Self::Branch {
+				set,
+				head,
+				synthetics,
+				..
+			} => (RegionKind::Branch, set, *head, synthetics),
+		};
+
+		let mut set = set.clone();
+
+		set.extend(synthetics.iter().copied());
+
+		Region { kind, start, set }
+	}
+}
+
+#[cfg(feature = "serde")]
+fn serialize_set<S: serde::Serializer>(
+	set: &Set,
+	serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+	serializer.collect_seq(set.ones())
+}
+
+/// A record of every pass made by the restructurers, along with a snapshot of the graph
+/// after each one. It is filled by passing it as the observer to `run_observed`.
+#[derive(Default)]
+pub struct Trace {
+	steps: Vec<Step>,
+	snapshots: Vec<Vec<u8>>,
+
+	writer: Writer,
+}
+
+impl Trace {
+	/// Creates a new, empty trace.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			steps: Vec::new(),
+			snapshots: Vec::new(),
+
+			writer: Writer::new(),
+		}
+	}
+
+	/// Returns the steps recorded so far, in the order they were made.
+	#[must_use]
+	pub fn steps(&self) -> &[Step] {
+		&self.steps
+	}
+
+	/// Returns the DOT snapshot of the whole graph taken after the step, if it was
+	/// made by a bulk restructurer.
+	#[must_use]
+	pub fn snapshot(&self, index: usize) -> Option<&[u8]> {
+		self.snapshots
+			.get(index)
+			.filter(|snapshot| !snapshot.is_empty())
+			.map(Vec::as_slice)
+	}
+
+	/// Removes all recorded steps.
+	pub fn clear(&mut self) {
+		self.steps.clear();
+		self.snapshots.clear();
+	}
+
+	/// Writes each snapshot to a numbered `step_NNNN.dot` file in the directory.
+	///
+	/// # Errors
+	///
+	/// Returns any error from creating or writing the files.
+	pub fn write_dot_files(&self, directory: &Path) -> Result<()> {
+		for (index, snapshot) in self.snapshots.iter().enumerate() {
+			if snapshot.is_empty() {
+				continue;
+			}
+
+			let path = directory.join(format!("step_{index:04}.dot"));
+
+			File::create(path)?.write_all(snapshot)?;
+		}
+
+		Ok(())
+	}
+
+	/// Writes the steps as a JSON array with one step per line.
+	///
+	/// # Errors
+	///
+	/// Returns any error from writing to the output.
+	#[cfg(feature = "serde")]
+	pub fn write_json<W: Write>(&self, out: W) -> Result<()> {
+		let mut out = std::io::BufWriter::new(out);
+
+		write!(out, "[")?;
+
+		for (index, step) in self.steps.iter().enumerate() {
+			let separator = if index == 0 { "\n" } else { ",\n" };
+
+			write!(out, "{separator}")?;
+
+			serde_json::to_writer(&mut out, step)?;
+		}
+
+		writeln!(out, "\n]")?;
+
+		out.flush()
+	}
+}
+
+impl<N> StructureObserver<N> for Trace
+where
+	N: Nodes + Synthetics,
+{
+	fn on_loop_found(&mut self, _: &N, set: Slice, entries: &[usize], exits: &[usize]) {
+		self.steps.push(Step::Loop {
+			set: set.ones().collect(),
+			entries: entries.to_vec(),
+			exits: exits.to_vec(),
+			synthetics: Vec::new(),
+		});
+	}
+
+	fn on_branch_head(
+		&mut self,
+		_: &N,
+		set: Slice,
+		head: usize,
+		_: &[Branch],
+		continuations: &[usize],
+	) {
+		self.steps.push(Step::Branch {
+			set: set.ones().collect(),
+			head,
+			continuations: continuations.to_vec(),
+			synthetics: Vec::new(),
+		});
+	}
+
+	fn on_synthetic(&mut self, _: &N, id: usize, _: Synthetic) {
+		if let Some(Step::Loop { synthetics, .. } | Step::Branch { synthetics, .. }) =
+			self.steps.last_mut()
+		{
+			synthetics.push(id);
+		}
+	}
+
+	fn on_restructured(&mut self, nodes: &N, set: Slice) {
+		let Some(step) = self.steps.last() else {
+			return;
+		};
+
+		let mut snapshot = Vec::new();
+		let regions = self.writer.regions_mut();
+
+		regions.clear();
+		regions.push(step.to_region());
+
+		// Writing to memory cannot fail
+		self.writer.run(nodes, set, &mut snapshot).unwrap();
+
+		self.snapshots.resize_with(self.steps.len() - 1, Vec::new);
+		self.snapshots.push(snapshot);
+	}
+}
@@ -0,0 +1,513 @@
+use crate::{
+	directed::{
+		dominator_finder::DominatorFinder, dominator_tree::DominatorTree,
+		strongly_connected_finder::StronglyConnectedFinder,
+	},
+	merge::{Construct, Header},
+	nodes::{Predecessors, Successors, Synthetic, Synthetics, Var},
+	set::{Set, Slice},
+};
+
+/// What a selection picks its successor by.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Selector {
+	/// A synthetic variable.
+	Var(Var),
+
+	/// The value computed by an original node, which is the index of its successor.
+	Node(usize),
+}
+
+/// An arm of a [`Statement::Select`], taken when the selector has any of the values.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Case {
+	pub values: Vec<usize>,
+	pub body: Vec<Statement>,
+}
+
+/// When a [`Statement::Loop`] goes back to its start.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Condition {
+	/// The loop has no exit.
+	Always,
+
+	/// The loop repeats while the selector has any of the values.
+	Select {
+		selector: Selector,
+		values: Vec<usize>,
+	},
+}
+
+/// A statement of a structured program.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Statement {
+	/// The contents of an original node.
+	Block(usize),
+
+	/// An assignment to a synthetic variable.
+	Assign { var: Var, value: usize },
+
+	/// Runs the body and then repeats it while the condition holds.
+	Loop {
+		body: Vec<Statement>,
+		condition: Condition,
+	},
+
+	/// Runs the case matching the selector. Values without a case are never selected.
+	Select {
+		selector: Selector,
+		cases: Vec<Case>,
+	},
+}
+
+/// The reasons a graph cannot be turned into a tree.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum Error {
+	/// The loop containing the node does not have exactly one entry.
+	Irreducible(usize),
+
+	/// The loop entered at the node is repeated from more than one place.
+	MultipleLatches(usize),
+
+	/// The node leaves a loop without being its latch.
+	LoopExit(usize),
+
+	/// The branch at the node continues to more than one node.
+	MultipleContinuations(usize),
+
+	/// The synthetic node has several successors but does not select between them.
+	NoSelector(usize),
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Irreducible(id) => write!(f, "loop at node {id} has more than one entry"),
+			Self::MultipleLatches(id) => write!(f, "loop at node {id} has more than one latch"),
+			Self::LoopExit(id) => write!(f, "node {id} leaves a loop but is not its latch"),
+			Self::MultipleContinuations(id) => {
+				write!(f, "branch at node {id} has more than one continuation")
+			}
+			Self::NoSelector(id) => write!(f, "node {id} has several successors but no selector"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// Returns the value a selection at the node must have to pick the successor at the
+/// index. Latches go back to their first successor while the repetition variable is
+/// `1`, and every other selection picks the successor at the index of its value.
+pub fn selected_value<N: Synthetics>(nodes: &N, id: usize, index: usize) -> usize {
+	if nodes.synthetic(id) == Some(Synthetic::Selection(Var::Repetition)) {
+		usize::from(index == 0)
+	} else {
+		index
+	}
+}
+
+/// This structure turns a structured graph, such as the output of the restructurers,
+/// into a tree of statements. Loops must have a single entry and a single latch that
+/// all exits leave from, and each branch must continue to at most one node.
+#[derive(Default)]
+pub struct Builder {
+	strongly_connected_finder: StronglyConnectedFinder,
+	dominator_finder: DominatorFinder,
+	dominator_tree: DominatorTree,
+
+	latches: Vec<usize>,
+	components: Vec<Set>,
+	headers: Vec<Header>,
+	reached: Set,
+	seen: Set,
+	stack: Vec<usize>,
+}
+
+impl Builder {
+	/// Creates a new instance of the builder.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			strongly_connected_finder: StronglyConnectedFinder::new(),
+			dominator_finder: DominatorFinder::new(),
+			dominator_tree: DominatorTree::new(),
+
+			latches: Vec::new(),
+			components: Vec::new(),
+			headers: Vec::new(),
+			reached: Set::new(),
+			seen: Set::new(),
+			stack: Vec::new(),
+		}
+	}
+
+	fn latch(&self, id: usize) -> Option<usize> {
+		self.latches
+			.get(id)
+			.copied()
+			.filter(|&latch| latch != usize::MAX)
+	}
+
+	fn set_latch(&mut self, header: usize, latch: usize) {
+		if self.latches.len() <= header {
+			self.latches.resize(header + 1, usize::MAX);
+		}
+
+		self.latches[header] = latch;
+	}
+
+	fn find_loop<N: Predecessors + Successors>(
+		&mut self,
+		nodes: &N,
+		component: &Set,
+		start: usize,
+	) -> Result<usize, Error> {
+		let mut entries = component
+			.ones()
+			.filter(|&id| id == start || nodes.predecessors(id).any(|id| !component[id]));
+
+		let header = match (entries.next(), entries.next()) {
+			(Some(header), None) => header,
+			(Some(header), Some(_)) => return Err(Error::Irreducible(header)),
+			(None, _) => return Err(Error::Irreducible(component.ones().next().unwrap())),
+		};
+
+		let mut latches = nodes.predecessors(header).filter(|&id| component[id]);
+
+		let latch = match (latches.next(), latches.next()) {
+			(Some(latch), None) => latch,
+			_ => return Err(Error::MultipleLatches(header)),
+		};
+
+		if let Some(exit) = component
+			.ones()
+			.find(|&id| id != latch && nodes.successors(id).any(|id| !component[id]))
+		{
+			return Err(Error::LoopExit(exit));
+		}
+
+		self.set_latch(header, latch);
+
+		Ok(header)
+	}
+
+	// The nested loops of a loop are the components left once its header is removed.
+	fn find_loops<N>(&mut self, nodes: &N, set: Slice, start: usize) -> Result<(), Error>
+	where
+		N: Predecessors + Successors,
+	{
+		self.latches.clear();
+		self.components.clear();
+
+		self.strongly_connected_finder.run(nodes, set, |component| {
+			self.components.push(component);
+		});
+
+		while let Some(mut component) = self.components.pop() {
+			let header = self.find_loop(nodes, &component, start)?;
+
+			component.remove(header);
+
+			self.strongly_connected_finder
+				.run(nodes, component.as_slice(), |component| {
+					self.components.push(component);
+				});
+		}
+
+		// Nodes that repeat themselves are not found as components
+		for id in set.ones() {
+			if self.latch(id).is_none() && nodes.successors(id).any(|successor| successor == id) {
+				self.set_latch(id, id);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn find_dominators<N>(&mut self, nodes: &N, set: Slice, start: usize)
+	where
+		N: Predecessors + Successors,
+	{
+		self.dominator_finder.run(nodes, set.ones(), start);
+		self.dominator_tree.load(&self.dominator_finder);
+
+		self.reached.clear();
+		self.reached
+			.extend(self.dominator_finder.ids().iter().copied());
+	}
+
+	// A successor only starts an arm if the head is its only predecessor, ignoring
+	// the ones it dominates as they can only reach it by repeating.
+	fn is_arm<N: Predecessors>(&self, nodes: &N, head: usize, id: usize) -> bool {
+		let mut predecessors = nodes
+			.predecessors(id)
+			.filter(|&predecessor| self.reached[predecessor])
+			.filter(|&predecessor| !self.dominator_tree.dominates(id, predecessor));
+
+		predecessors.next() == Some(head) && predecessors.next().is_none()
+	}
+
+	fn add_continuation(head: usize, merge: &mut Option<usize>, id: usize) -> Result<(), Error> {
+		match *merge {
+			None => *merge = Some(id),
+			Some(merge) if merge == id => {}
+			Some(_) => return Err(Error::MultipleContinuations(head)),
+		}
+
+		Ok(())
+	}
+
+	// The continuation is the only node reached from the head or its arms that is
+	// not itself in an arm, ignoring edges that repeat a loop.
+	fn find_merge<N>(
+		&mut self,
+		nodes: &N,
+		head: usize,
+		targets: &[(usize, usize)],
+	) -> Result<Option<usize>, Error>
+	where
+		N: Predecessors + Successors,
+	{
+		let mut merge = None;
+
+		self.seen.clear();
+
+		for &(_, target) in targets {
+			if !self.is_arm(nodes, head, target) {
+				Self::add_continuation(head, &mut merge, target)?;
+
+				continue;
+			}
+
+			self.stack.push(target);
+
+			while let Some(id) = self.stack.pop() {
+				for successor in nodes.successors(id) {
+					if self.dominator_tree.dominates(successor, id) {
+						continue;
+					}
+
+					if !self.dominator_tree.dominates(target, successor) {
+						Self::add_continuation(head, &mut merge, successor)?;
+					} else if !self.seen.insert(successor) {
+						self.stack.push(successor);
+					}
+				}
+			}
+		}
+
+		Ok(merge)
+	}
+
+	fn find_selector<N: Synthetics>(nodes: &N, id: usize) -> Result<Selector, Error> {
+		match nodes.synthetic(id) {
+			None => Ok(Selector::Node(id)),
+			Some(Synthetic::Selection(var)) => Ok(Selector::Var(var)),
+			Some(_) => Err(Error::NoSelector(id)),
+		}
+	}
+
+	// Builds the selection at the head over the given successors, returning where the
+	// sequence carries on afterwards.
+	fn build_branch<N>(
+		&mut self,
+		nodes: &N,
+		head: usize,
+		targets: &[(usize, usize)],
+		statements: &mut Vec<Statement>,
+		latch: usize,
+	) -> Result<Option<usize>, Error>
+	where
+		N: Predecessors + Successors + Synthetics,
+	{
+		let Some(&(_, first)) = targets.first() else {
+			return Ok(None);
+		};
+
+		if targets.iter().all(|&(_, target)| target == first) {
+			return Ok(Some(first));
+		}
+
+		let selector = Self::find_selector(nodes, head)?;
+		let merge = self.find_merge(nodes, head, targets)?;
+		let stop = merge.unwrap_or(usize::MAX);
+
+		// Arms joining outside the head leave to an enclosing construct
+		self.headers.push(Header {
+			id: head,
+			construct: Construct::Selection,
+			merge: merge.filter(|&merge| self.dominator_tree.dominates(head, merge)),
+		});
+
+		let mut cases: Vec<(usize, Case)> = Vec::new();
+
+		for &(index, target) in targets {
+			let value = selected_value(nodes, head, index);
+
+			if let Some((_, case)) = cases.iter_mut().find(|(id, _)| *id == target) {
+				case.values.push(value);
+
+				continue;
+			}
+
+			let body = self.build_sequence(nodes, target, stop, latch, false)?;
+
+			cases.push((
+				target,
+				Case {
+					values: vec![value],
+					body,
+				},
+			));
+		}
+
+		statements.push(Statement::Select {
+			selector,
+			cases: cases.into_iter().map(|(_, case)| case).collect(),
+		});
+
+		Ok(merge)
+	}
+
+	// A node is inside the loop if it lies on a path from the header to the latch
+	fn is_inside(&self, header: usize, latch: usize, id: usize) -> bool {
+		self.dominator_tree.dominates(header, id) && self.dominator_tree.dominates(id, latch)
+	}
+
+	fn build_loop<N>(
+		&mut self,
+		nodes: &N,
+		header: usize,
+		latch: usize,
+		statements: &mut Vec<Statement>,
+		outer: usize,
+	) -> Result<Option<usize>, Error>
+	where
+		N: Predecessors + Successors + Synthetics,
+	{
+		// Successors inside the loop other than the header repeat inner loops sharing
+		// the latch, such as a latch that repeats itself, and are built in the body
+		let (repeats, exits): (Vec<_>, Vec<_>) = nodes
+			.successors(latch)
+			.enumerate()
+			.filter(|&(_, id)| id == header || !self.is_inside(header, latch, id))
+			.partition(|&(_, id)| id == header);
+
+		let merge = match exits.first() {
+			Some(&(_, first)) if latch != outer => {
+				Some(first).filter(|&first| exits.iter().all(|&(_, id)| id == first))
+			}
+			_ => None,
+		};
+
+		self.headers.push(Header {
+			id: header,
+			construct: Construct::Loop {
+				continue_target: latch,
+			},
+			merge,
+		});
+
+		let body = self.build_sequence(nodes, header, usize::MAX, latch, true)?;
+		let condition = if exits.is_empty() {
+			Condition::Always
+		} else {
+			Condition::Select {
+				selector: Self::find_selector(nodes, latch)?,
+				values: repeats
+					.iter()
+					.map(|&(index, _)| selected_value(nodes, latch, index))
+					.collect(),
+			}
+		};
+
+		statements.push(Statement::Loop { body, condition });
+
+		// The exits of a loop at the latch of another are left to the outer loop
+		if latch == outer {
+			return Ok(None);
+		}
+
+		self.build_branch(nodes, latch, &exits, statements, outer)
+	}
+
+	fn build_sequence<N>(
+		&mut self,
+		nodes: &N,
+		mut id: usize,
+		stop: usize,
+		latch: usize,
+		mut is_entered: bool,
+	) -> Result<Vec<Statement>, Error>
+	where
+		N: Predecessors + Successors + Synthetics,
+	{
+		let mut statements = Vec::new();
+
+		while id != stop {
+			if let Some(inner) = self.latch(id).filter(|_| !is_entered) {
+				match self.build_loop(nodes, id, inner, &mut statements, latch)? {
+					Some(next) => id = next,
+					None => break,
+				}
+
+				continue;
+			}
+
+			is_entered = false;
+
+			match nodes.synthetic(id) {
+				None => statements.push(Statement::Block(id)),
+				Some(Synthetic::Assignment(var, value)) => {
+					statements.push(Statement::Assign { var, value });
+				}
+				Some(Synthetic::NoOperation | Synthetic::Selection(_)) => {}
+			}
+
+			if id == latch {
+				break;
+			}
+
+			let targets: Vec<_> = nodes.successors(id).enumerate().collect();
+
+			match self.build_branch(nodes, id, &targets, &mut statements, latch)? {
+				Some(next) => id = next,
+				None => break,
+			}
+		}
+
+		Ok(statements)
+	}
+
+	/// Returns the headers of the loops and selections, in the order they were built.
+	/// Merge blocks are the nodes the constructs meet at when their header dominates
+	/// them, and no blocks are added where one is missing.
+	#[must_use]
+	pub fn headers(&self) -> &[Header] {
+		&self.headers
+	}
+
+	/// Builds the tree for the nodes in the set reached from the start.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a loop or branch is not structured.
+	pub fn run<N>(&mut self, nodes: &N, set: Slice, start: usize) -> Result<Vec<Statement>, Error>
+	where
+		N: Predecessors + Successors + Synthetics,
+	{
+		self.headers.clear();
+
+		self.find_loops(nodes, set, start)?;
+		self.find_dominators(nodes, set, start);
+
+		self.build_sequence(nodes, start, usize::MAX, usize::MAX, false)
+	}
+}
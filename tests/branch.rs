@@ -0,0 +1,130 @@
+use perfect_reconstructibility::{
+	interpreter::Interpreter,
+	list::List,
+	nodes::{Successors, Synthetic, Synthetics, Var},
+	structurer::{branch, repeat},
+	tree::Builder,
+};
+
+fn trace(list: &List, choices: &[usize]) -> Vec<usize> {
+	let mut interpreter = Interpreter::new();
+
+	interpreter
+		.run(list, 0, 16, |id, count| choices[id] % count)
+		.unwrap();
+
+	interpreter.trace().to_vec()
+}
+
+// Runs every combination of choices, as the nodes have no more than three successors
+fn assert_same_traces(list: &List, original: &List) {
+	let combinations = 3_usize.pow(original.len().try_into().unwrap());
+
+	for combination in 0..combinations {
+		let choices: Vec<_> = (0..original.len())
+			.map(|id| combination / 3_usize.pow(id.try_into().unwrap()) % 3)
+			.collect();
+
+		assert_eq!(trace(list, &choices), trace(original, &choices));
+	}
+}
+
+fn assert_structured(len: usize, edges: &[(usize, usize)]) -> List {
+	let original = List::from_edges(len, edges);
+	let mut list = original.clone();
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+	branch::Bulk::new().run(&mut list, &mut set, 0);
+
+	let ids = list.ids();
+
+	assert!(Builder::new().run(&list, ids.as_slice(), 0).is_ok());
+
+	assert_same_traces(&list, &original);
+
+	list
+}
+
+// The loop of 1 and 2 leaves to 3 and 4, and 4 is also reached from 3. The selection
+// picking the exit of the loop is found past its latch and restructured.
+#[test]
+fn branch_head_walks_past_loop_latches() {
+	let list = assert_structured(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (1, 3), (1, 4), (2, 1)]);
+	let exit = list
+		.ids()
+		.ones()
+		.find(|&id| list.synthetic(id) == Some(Synthetic::Selection(Var::Destination)))
+		.unwrap();
+
+	for id in list.successors(exit) {
+		assert!(matches!(
+			list.synthetic(id),
+			Some(Synthetic::Assignment(Var::Branch, _))
+		));
+		assert_eq!(
+			find_dispatched(&list, id),
+			Some(Synthetic::Selection(Var::Branch))
+		);
+	}
+}
+
+#[test]
+fn branch_set_includes_exit() {
+	assert_structured(4, &[(0, 1), (1, 2), (2, 3), (1, 1), (2, 3), (3, 1), (2, 1)]);
+}
+
+// Follows the funnels after a branch assignment to the selection it is meant for
+fn find_dispatched(list: &List, mut id: usize) -> Option<Synthetic> {
+	loop {
+		id = list.successors(id).next()?;
+
+		match list.synthetic(id) {
+			Some(Synthetic::NoOperation) => {}
+			synthetic => return synthetic,
+		}
+	}
+}
+
+// Node 1 heads a branch nested in that of 0, and reaches both of the continuations of
+// 0 through 2 and 3. The assignments picking them must stay next to the outer
+// selection, or the inner assignments would overwrite them on the way.
+#[test]
+fn outer_dispatch_stays_with_selection() {
+	let original = List::from_edges(
+		6,
+		&[
+			(0, 1),
+			(0, 3),
+			(1, 3),
+			(1, 2),
+			(1, 4),
+			(2, 4),
+			(2, 5),
+			(3, 5),
+		],
+	);
+	let mut list = original.clone();
+	let mut set = list.ids();
+
+	branch::Bulk::new().run(&mut list, &mut set, 0);
+
+	let selections = set
+		.ones()
+		.filter(|&id| list.synthetic(id) == Some(Synthetic::Selection(Var::Branch)))
+		.count();
+
+	assert_eq!(selections, 2);
+
+	for id in set.ones() {
+		if let Some(Synthetic::Assignment(Var::Branch, _)) = list.synthetic(id) {
+			assert_eq!(
+				find_dispatched(&list, id),
+				Some(Synthetic::Selection(Var::Branch)),
+				"assignment {id} is overwritten before its selection"
+			);
+		}
+	}
+
+	assert_same_traces(&list, &original);
+}
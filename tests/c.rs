@@ -0,0 +1,315 @@
+use std::{
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+use perfect_reconstructibility::{
+	emit::{
+		c::{Collapsed, Emitter, Unstructured},
+		Blocks,
+	},
+	interpreter::Interpreter,
+	list::List,
+	nodes::Successors,
+	set::Set,
+	structurer::{branch, repeat},
+	tree::Builder,
+};
+
+const LIMIT: usize = 200;
+const SEEDS: [u32; 4] = [0, 1, 7, 1234];
+
+// Each original node is traced and picks its successor from a hash of how many times
+// it was visited, so that evaluating a selector twice gives the same choice
+const PRELUDE: &str = "#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+static uint32_t seed;
+static uint32_t visits[4096];
+static unsigned steps;
+
+static void trace(uint32_t id) {
+	if (steps == LIMIT) {
+		exit(0);
+	}
+
+	steps++;
+	visits[id]++;
+	printf(\"%u\\n\", id);
+}
+
+static int choose(uint32_t id, uint32_t count) {
+	uint32_t hash = ((id + 1) * 2654435761u) ^ (visits[id] * 40503u) ^ seed;
+
+	return (int)(hash % count);
+}
+";
+
+fn choose(id: usize, count: usize, visits: u32, seed: u32) -> usize {
+	let id = u32::try_from(id).unwrap();
+	let hash = (id + 1).wrapping_mul(2_654_435_761) ^ visits.wrapping_mul(40503) ^ seed;
+
+	usize::try_from(hash).unwrap() % count
+}
+
+struct Program<'a> {
+	list: &'a List,
+}
+
+impl Blocks for Program<'_> {
+	fn contents(&mut self, id: usize) -> String {
+		format!("trace({id});")
+	}
+
+	fn selector(&mut self, id: usize) -> String {
+		format!("choose({id}, {})", self.list.successors(id).count())
+	}
+}
+
+fn find_compiler() -> Option<String> {
+	let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".into());
+	let found = Command::new(&compiler).arg("--version").output();
+
+	found
+		.is_ok_and(|output| output.status.success())
+		.then_some(compiler)
+}
+
+// Compiles the body into a program in its own directory and returns its path
+fn compile(compiler: &str, name: &str, body: &str) -> PathBuf {
+	let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+	let source = directory.join("main.c");
+	let program = directory.join("main");
+	let code = format!(
+		"#define LIMIT {LIMIT}\n{PRELUDE}\nstatic void run(void) {{\n{body}}}\n\nint main(int argc, char **argv) {{\n\tseed = (uint32_t)atoi(argv[1]);\n\trun();\n\treturn 0;\n}}\n"
+	);
+
+	std::fs::create_dir_all(&directory).unwrap();
+	std::fs::write(&source, code).unwrap();
+
+	let status = Command::new(compiler)
+		.arg("-o")
+		.arg(&program)
+		.arg(&source)
+		.status()
+		.unwrap();
+
+	assert!(status.success(), "{name} does not compile");
+
+	program
+}
+
+fn run_program(program: &Path, seed: u32) -> Vec<usize> {
+	let output = Command::new(program)
+		.arg(seed.to_string())
+		.output()
+		.unwrap();
+
+	assert!(output.status.success());
+
+	String::from_utf8(output.stdout)
+		.unwrap()
+		.lines()
+		.map(|line| line.parse().unwrap())
+		.collect()
+}
+
+fn run_interpreter(list: &List, seed: u32) -> Vec<usize> {
+	let mut interpreter = Interpreter::new();
+	let mut visits = vec![0; list.len()];
+
+	interpreter
+		.run(list, 0, LIMIT, |id, count| {
+			visits[id] += 1;
+
+			choose(id, count, visits[id], seed)
+		})
+		.unwrap();
+
+	interpreter.trace().to_vec()
+}
+
+fn check_program(compiler: &str, name: &str, body: &str, list: &List) {
+	let program = compile(compiler, name, body);
+
+	for seed in SEEDS {
+		assert_eq!(
+			run_program(&program, seed),
+			run_interpreter(list, seed),
+			"{name} differs from the interpreter with seed {seed}"
+		);
+	}
+}
+
+fn write_structured(list: &List) -> String {
+	let statements = Builder::new().run(list, list.ids().as_slice(), 0).unwrap();
+	let mut out = String::new();
+
+	Emitter::new()
+		.run(&statements, &mut Program { list }, &mut out)
+		.unwrap();
+
+	out
+}
+
+fn write_unstructured(list: &List) -> String {
+	let mut out = String::new();
+
+	Emitter::new()
+		.run_unstructured(
+			list,
+			list.ids().as_slice(),
+			0,
+			&mut Program { list },
+			&mut out,
+		)
+		.unwrap();
+
+	out
+}
+
+fn restructure(list: &List) -> List {
+	let mut list = list.clone();
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+	branch::Bulk::new().run(&mut list, &mut set, 0);
+
+	list
+}
+
+#[cfg(feature = "generator")]
+#[test]
+fn emitted_code_matches_interpreter() {
+	use perfect_reconstructibility::generator::{Generator, Parameters};
+
+	let Some(compiler) = find_compiler() else {
+		eprintln!("no C compiler found, skipping");
+
+		return;
+	};
+
+	let parameters = Parameters {
+		nodes: 24,
+		back_edges: 0.2,
+		irreducibility: 0.3,
+		max_degree: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..4 {
+		let list = Generator::new(seed).run(&parameters).unwrap();
+		let restructured = restructure(&list);
+
+		check_program(
+			&compiler,
+			&format!("structured_{seed}"),
+			&write_structured(&restructured),
+			&list,
+		);
+		check_program(
+			&compiler,
+			&format!("unstructured_{seed}"),
+			&write_unstructured(&list),
+			&list,
+		);
+	}
+}
+
+// The loop from 1 to 2 is entered at both nodes and left from both
+#[test]
+fn irreducible_code_matches_interpreter() {
+	let list = List::from_edges(5, &[(0, 1), (0, 2), (1, 2), (2, 1), (1, 3), (2, 4), (3, 4)]);
+
+	let Some(compiler) = find_compiler() else {
+		eprintln!("no C compiler found, skipping");
+
+		return;
+	};
+
+	let restructured = restructure(&list);
+
+	check_program(
+		&compiler,
+		"irreducible_structured",
+		&write_structured(&restructured),
+		&list,
+	);
+	check_program(
+		&compiler,
+		"irreducible_unstructured",
+		&write_unstructured(&list),
+		&list,
+	);
+}
+
+// The loop from 1 to 3 has two entries and is left unstructured, inside an outer
+// loop that is structured
+#[test]
+fn mixed_code_matches_interpreter() {
+	let list = List::from_edges(
+		6,
+		&[
+			(0, 1),
+			(1, 2),
+			(1, 3),
+			(2, 3),
+			(3, 2),
+			(2, 4),
+			(3, 4),
+			(4, 0),
+			(4, 5),
+		],
+	);
+	let set: Set = [1, 2, 3].into_iter().collect();
+	let regions = [Unstructured::new(&list, set, 1).unwrap()];
+
+	assert_eq!(regions[0].exit(), Some(4));
+
+	let collapsed = Collapsed::new(&list, &regions);
+	let statements = Builder::new()
+		.run(&collapsed, list.ids().as_slice(), 0)
+		.unwrap();
+	let mut out = String::new();
+
+	Emitter::new()
+		.run_mixed(
+			&statements,
+			&list,
+			&regions,
+			&mut Program { list: &list },
+			&mut out,
+		)
+		.unwrap();
+
+	assert!(out.contains("do {"));
+	assert!(out.contains("goto end_1;"));
+
+	let Some(compiler) = find_compiler() else {
+		eprintln!("no C compiler found, skipping");
+
+		return;
+	};
+
+	check_program(&compiler, "mixed", &out, &list);
+}
+
+#[test]
+fn regions_need_one_entry_and_exit() {
+	let list = List::from_edges(5, &[(0, 1), (0, 2), (1, 2), (2, 1), (1, 3), (2, 4)]);
+	let set = |ids: &[usize]| ids.iter().copied().collect::<Set>();
+
+	assert!(Unstructured::new(&list, set(&[1, 2]), 1).is_none());
+	assert!(Unstructured::new(&list, set(&[0, 1, 2]), 0).is_none());
+	assert!(Unstructured::new(&list, set(&[0, 1, 2, 3, 4]), 0).is_some());
+	assert!(Unstructured::new(&list, set(&[1]), 2).is_none());
+}
+
+#[test]
+fn jump_tables_end_in_abort() {
+	let list = List::from_edges(4, &[(0, 1), (0, 2), (0, 3)]);
+	let out = write_unstructured(&list);
+
+	assert!(out.contains("\tdefault: abort();\n"));
+}
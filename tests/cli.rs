@@ -0,0 +1,130 @@
+#![cfg(feature = "cli")]
+
+use std::{
+	io::Write,
+	process::{Command, Output, Stdio},
+};
+
+fn run(args: &[&str], input: &str) -> Output {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_perfect-reconstructibility"))
+		.args(args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	child
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(input.as_bytes())
+		.unwrap();
+
+	child.wait_with_output().unwrap()
+}
+
+fn run_ok(args: &[&str], input: &str) -> String {
+	let output = run(args, input);
+
+	assert!(
+		output.status.success(),
+		"{args:?} failed: {}",
+		String::from_utf8_lossy(&output.stderr)
+	);
+
+	String::from_utf8(output.stdout).unwrap()
+}
+
+fn run_error(args: &[&str], input: &str) -> String {
+	let output = run(args, input);
+
+	assert!(!output.status.success(), "{args:?} succeeded");
+	assert!(output.stdout.is_empty());
+
+	String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn gen_reports_bad_options() {
+	let cases = [
+		(&["gen", "--exits", "0"][..], "`--exits`"),
+		(&["gen", "--nodes", "1"], "`--exits`"),
+		(&["gen", "--max-degree", "0"], "`--max-degree`"),
+		(
+			&["gen", "--back-edges", "1.5"],
+			"`--back-edges` must be between 0 and 1",
+		),
+		(&["gen", "--irreducibility", "-1"], "`--irreducibility`"),
+		(&["gen", "--nodes", "many"], "`--nodes`"),
+		(&["gen", "--colour"], "unknown option `--colour`"),
+	];
+
+	for (args, message) in cases {
+		let error = run_error(args, "");
+
+		assert!(error.starts_with("error: "), "{error}");
+		assert!(error.contains(message), "{args:?} gave {error}");
+	}
+}
+
+#[test]
+fn gen_writes_graphs_that_restructure() {
+	let graph = run_ok(&["gen", "--seed", "3", "--nodes", "16", "--to", "json"], "");
+	let structured = run_ok(&["structure"], &graph);
+
+	assert_eq!(run_ok(&["verify", "--spirv"], &structured), "structured\n");
+}
+
+// Node 3 is not reached from node 0, and is written as a region of its own
+#[test]
+fn emit_writes_separate_regions() {
+	let graph = "digraph { 0 -> 1; 1 -> 2; 0 -> 2; 3 -> 4; 4 -> 2 }";
+
+	let error = run_error(&["emit"], graph);
+
+	assert!(error.contains("unreachable nodes: 3 4"), "{error}");
+
+	let out = run_ok(&["emit", "--unreachable", "separate"], graph);
+
+	assert!(out.contains("// region starting at node 3"), "{out}");
+	assert!(out.contains("block_3();"), "{out}");
+	assert!(out.contains("block_4();"), "{out}");
+
+	let out = run_ok(&["emit", "--unreachable", "prune"], graph);
+
+	assert!(!out.contains("block_3();"), "{out}");
+}
+
+// Restructures without errors, but the result is not accepted by the tree builder
+const NOT_STRUCTURED: &str = "digraph {
+	0 -> 1; 1 -> 2; 2 -> 3; 3 -> 4; 4 -> 5; 5 -> 6; 6 -> 7; 7 -> 8; 8 -> 9;
+	9 -> 10; 10 -> 11; 11 -> 12; 12 -> 13; 13 -> 14; 8 -> 11; 8 -> 3; 4 -> 6;
+	1 -> 14; 9 -> 14; 5 -> 7; 8 -> 13;
+}";
+
+#[test]
+fn minimize_keeps_the_failure() {
+	let output = run(&["minimize", "--check", "verify"], NOT_STRUCTURED);
+
+	assert!(output.status.success());
+
+	let minimized = String::from_utf8(output.stdout).unwrap();
+	let structured = run_ok(&["structure"], &minimized);
+
+	assert!(run_error(&["verify"], &structured).starts_with("error: "));
+	assert!(minimized.matches("->").count() < 21);
+}
+
+#[test]
+fn minimize_needs_a_failing_graph() {
+	for check in ["panic", "verify", "trace"] {
+		let error = run_error(&["minimize", "--check", check], "digraph { 0 -> 1 }");
+
+		assert!(error.contains("does not fail the check"), "{error}");
+	}
+
+	let error = run_error(&["minimize", "--check", "crash"], "digraph { 0 -> 1 }");
+
+	assert!(error.contains("unknown check `crash`"), "{error}");
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "generator")]
+
+use perfect_reconstructibility::{
+	directed::{
+		dominator_finder::{Algorithm, DominatorFinder},
+		dominator_tree::DominatorTree,
+		strongly_connected_finder::StronglyConnectedFinder,
+	},
+	generator::{Generator, Parameters},
+	list::List,
+	nodes::Successors,
+	set::Set,
+};
+
+const SEEDS: u64 = 64;
+
+fn generate(seed: u64) -> List {
+	let parameters = Parameters {
+		nodes: 24,
+		back_edges: 0.25,
+		irreducibility: 0.5,
+		max_degree: 3,
+		exits: 2,
+	};
+
+	Generator::new(seed).run(&parameters).unwrap()
+}
+
+// Picks a start and a set holding it and about three in four of the other nodes
+fn pick_set(list: &List, seed: u64) -> (usize, Set) {
+	let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+	let mut next = || {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state
+	};
+
+	let start = usize::try_from(next() % 4).unwrap();
+	let set = (0..list.len())
+		.filter(|&id| id == start || next() % 4 != 0)
+		.collect();
+
+	(start, set)
+}
+
+// Finds the nodes of the set reached from the start without passing the removed one
+fn find_reached(list: &List, set: &Set, start: usize, removed: usize) -> Vec<bool> {
+	let mut reached = vec![false; list.len()];
+	let mut stack = vec![start];
+
+	while let Some(id) = stack.pop() {
+		if id == removed || !set[id] || std::mem::replace(&mut reached[id], true) {
+			continue;
+		}
+
+		stack.extend(list.successors(id));
+	}
+
+	reached
+}
+
+// A node strictly dominates another if removing it leaves the other unreached
+fn find_dominators(list: &List, set: &Set, start: usize, reached: &[bool]) -> Vec<Vec<usize>> {
+	let mut dominators = vec![Vec::new(); list.len()];
+
+	for dominator in (0..list.len()).filter(|&id| reached[id]) {
+		let without = find_reached(list, set, start, dominator);
+
+		for id in (0..list.len()).filter(|&id| reached[id] && id != dominator && !without[id]) {
+			dominators[id].push(dominator);
+		}
+	}
+
+	dominators
+}
+
+// Two nodes share a component if they reach each other, and only components with
+// more than one node are reported
+fn find_components(list: &List, set: &Set) -> Vec<Vec<usize>> {
+	let reached: Vec<_> = (0..list.len())
+		.map(|id| find_reached(list, set, id, usize::MAX))
+		.collect();
+
+	let mut components = Vec::new();
+	let mut seen = vec![false; list.len()];
+
+	for id in set.ones() {
+		if seen[id] {
+			continue;
+		}
+
+		let component: Vec<_> = set
+			.ones()
+			.filter(|&other| reached[id][other] && reached[other][id])
+			.collect();
+
+		for &id in &component {
+			seen[id] = true;
+		}
+
+		if component.len() > 1 {
+			components.push(component);
+		}
+	}
+
+	components.sort_unstable();
+	components
+}
+
+fn check_dominators(list: &List, set: &Set, start: usize) {
+	let mut iterative = DominatorFinder::new();
+	let mut semi_nca = DominatorFinder::new();
+	let mut tree = DominatorTree::new();
+
+	iterative.run_with(list, set.ones(), start, Algorithm::Iterative);
+	semi_nca.run_with(list, set.ones(), start, Algorithm::SemiNca);
+	tree.load(&iterative);
+
+	let reached = find_reached(list, set, start, usize::MAX);
+	let dominators = find_dominators(list, set, start, &reached);
+
+	for finder in [&iterative, &semi_nca] {
+		let mut ids = finder.ids().to_vec();
+
+		ids.sort_unstable();
+
+		assert!(ids
+			.into_iter()
+			.eq((0..list.len()).filter(|&id| reached[id])));
+	}
+
+	for id in 0..list.len() {
+		// The immediate dominator is the strict dominator the others also dominate
+		let expected = dominators[id]
+			.iter()
+			.copied()
+			.find(|&dominator| dominators[dominator].len() + 1 == dominators[id].len());
+
+		assert_eq!(iterative.dominator(id), expected, "dominator of node {id}");
+		assert_eq!(semi_nca.dominator(id), expected, "dominator of node {id}");
+	}
+
+	for dominator in (0..list.len()).filter(|&id| reached[id]) {
+		for id in (0..list.len()).filter(|&id| reached[id]) {
+			let expected = dominator == id || dominators[id].contains(&dominator);
+
+			assert_eq!(iterative.dominates(dominator, id), expected);
+			assert_eq!(tree.dominates(dominator, id), expected);
+		}
+	}
+}
+
+fn check_components(list: &List, set: &Set) {
+	let mut components = Vec::new();
+
+	StronglyConnectedFinder::new().run(list, set.as_slice(), |component| {
+		components.push(component.ones().collect::<Vec<_>>());
+	});
+
+	components.sort_unstable();
+
+	assert_eq!(components, find_components(list, set));
+}
+
+#[test]
+fn dominators_match_path_removal() {
+	for seed in 0..SEEDS {
+		let list = generate(seed);
+
+		check_dominators(&list, &list.ids(), 0);
+	}
+}
+
+#[test]
+fn dominators_match_path_removal_within_sets() {
+	for seed in 0..SEEDS {
+		let list = generate(seed);
+		let (start, set) = pick_set(&list, seed);
+
+		check_dominators(&list, &set, start);
+	}
+}
+
+#[test]
+fn components_match_mutual_reachability() {
+	for seed in 0..SEEDS {
+		let list = generate(seed);
+
+		check_components(&list, &list.ids());
+	}
+}
+
+#[test]
+fn components_match_mutual_reachability_within_sets() {
+	for seed in 0..SEEDS {
+		let list = generate(seed);
+		let (_, set) = pick_set(&list, seed);
+
+		check_components(&list, &set);
+	}
+}
@@ -0,0 +1,241 @@
+use perfect_reconstructibility::{
+	directed::dominator_finder::{Algorithm, DominatorFinder},
+	list::List,
+	nodes::{Predecessors, Successors},
+	set::Set,
+	structurer::{
+		branch::{self, Single},
+		repeat,
+	},
+};
+
+const GRAPHS: &[(usize, &[(usize, usize)])] = &[
+	(4, &[(0, 1), (0, 2), (1, 3), (2, 3)]),
+	(6, &[(0, 1), (0, 2), (1, 3), (1, 4), (2, 4), (3, 5), (4, 5)]),
+	(
+		7,
+		&[
+			(0, 1),
+			(0, 2),
+			(0, 3),
+			(1, 4),
+			(2, 4),
+			(2, 5),
+			(3, 5),
+			(4, 6),
+			(5, 6),
+		],
+	),
+	(
+		8,
+		&[
+			(0, 1),
+			(1, 2),
+			(1, 3),
+			(2, 4),
+			(3, 4),
+			(3, 5),
+			(4, 6),
+			(5, 6),
+			(5, 7),
+			(6, 7),
+			(6, 1),
+		],
+	),
+];
+
+// Cycles with several entries, which the iterative algorithm needs more passes for
+const IRREDUCIBLE: &[(usize, &[(usize, usize)])] = &[
+	(3, &[(0, 1), (0, 2), (1, 2), (2, 1)]),
+	(
+		6,
+		&[
+			(0, 1),
+			(0, 2),
+			(1, 3),
+			(2, 4),
+			(3, 4),
+			(4, 3),
+			(4, 5),
+			(3, 1),
+		],
+	),
+];
+
+// Returns whether the node can be reached without going through the blocker, from the
+// head or from any node of the set with a predecessor outside of it
+fn is_reachable_around(list: &List, set: &Set, head: usize, blocker: usize, id: usize) -> bool {
+	let mut seen = vec![false; list.len()];
+	let mut pending: Vec<_> = set
+		.ones()
+		.filter(|&id| id == head || list.predecessors(id).any(|id| !set[id]))
+		.filter(|&id| id != blocker)
+		.collect();
+
+	while let Some(next) = pending.pop() {
+		if std::mem::replace(&mut seen[next], true) {
+			continue;
+		}
+
+		pending.extend(
+			list.successors(next)
+				.filter(|&id| set[id] && id != blocker && !seen[id]),
+		);
+	}
+
+	seen[id]
+}
+
+// The branches of a head are found by asking which of its successors dominate each
+// node of the set. Nodes also entered from outside the set are dominated by none.
+fn assert_dominators_match(list: &List, single: &Single, set: &Set, head: usize) {
+	for start in list.successors(head).filter(|&id| id != head && set[id]) {
+		for id in set.ones() {
+			let expected = id == start || !is_reachable_around(list, set, head, start, id);
+
+			assert_eq!(
+				single.dominators().dominates_within(start, id, head),
+				expected,
+				"dominance of node {id} by {start} differs"
+			);
+		}
+	}
+}
+
+// Restructures the list like `branch::Bulk`, checking the kept dominators against
+// ones found from scratch before every step
+fn check_incremental(list: &mut List) {
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(list, &mut set);
+
+	let mut single = Single::new();
+	let mut pending = vec![(set.clone(), 0)];
+
+	single.find_dominators(list, set.as_slice(), 0);
+
+	while let Some((mut set, mut start)) = pending.pop() {
+		// Walk to the next node with several successors in the set
+		let head = loop {
+			let mut successors = list.successors(start).filter(|&id| id != start && set[id]);
+			let Some(successor) = successors.next() else {
+				break None;
+			};
+
+			if successors.next().is_some() {
+				break Some(start);
+			}
+
+			set.remove(start);
+			start = successor;
+		};
+
+		let Some(head) = head else {
+			continue;
+		};
+
+		assert_dominators_match(list, &single, &set, head);
+
+		if let Some(exit) = single.run_incremental(list, set.as_slice(), head) {
+			pending.push((std::mem::take(single.tail_mut()), exit));
+
+			for branch in single.branches_mut().iter_mut() {
+				branch.set.insert(exit);
+			}
+		}
+
+		for branch in single.branches_mut().drain(..) {
+			pending.push((branch.set, branch.start));
+		}
+	}
+}
+
+fn check_algorithms(list: &List) {
+	let mut iterative = DominatorFinder::new();
+	let mut semi_nca = DominatorFinder::new();
+
+	iterative.run_with(list, list.ids().ones(), 0, Algorithm::Iterative);
+	semi_nca.run_with(list, list.ids().ones(), 0, Algorithm::SemiNca);
+
+	assert_eq!(iterative.ids(), semi_nca.ids());
+
+	for &id in iterative.ids() {
+		assert_eq!(
+			iterative.dominator(id),
+			semi_nca.dominator(id),
+			"dominator of node {id} differs"
+		);
+	}
+}
+
+fn check_recomputed(list: &List) {
+	let mut incremental = list.clone();
+	let mut set = incremental.ids();
+
+	repeat::Bulk::new().run(&mut incremental, &mut set);
+	branch::Bulk::new().run(&mut incremental, &mut set, 0);
+
+	let mut recomputed = list.clone();
+	let mut set = recomputed.ids();
+
+	repeat::Bulk::new().run(&mut recomputed, &mut set);
+	branch::Bulk::new().run_recomputed(&mut recomputed, &mut set, 0);
+
+	assert!(incremental == recomputed, "restructured graphs differ");
+}
+
+#[test]
+fn incremental_dominators_match_recomputed() {
+	for &(len, edges) in GRAPHS {
+		check_incremental(&mut List::from_edges(len, edges));
+	}
+}
+
+#[test]
+fn semi_nca_matches_iterative() {
+	for &(len, edges) in GRAPHS.iter().chain(IRREDUCIBLE) {
+		check_algorithms(&List::from_edges(len, edges));
+	}
+}
+
+#[test]
+fn recomputed_run_matches_incremental() {
+	for &(len, edges) in GRAPHS {
+		check_recomputed(&List::from_edges(len, edges));
+	}
+}
+
+#[cfg(feature = "generator")]
+#[test]
+fn incremental_dominators_match_recomputed_in_generated_graphs() {
+	use perfect_reconstructibility::generator::{Generator, Parameters};
+
+	let parameters = Parameters {
+		nodes: 48,
+		max_degree: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..16 {
+		let list = Generator::new(seed).run(&parameters).unwrap();
+
+		check_recomputed(&list);
+		check_incremental(&mut list.clone());
+	}
+}
+
+#[cfg(feature = "generator")]
+#[test]
+fn semi_nca_matches_iterative_in_generated_graphs() {
+	use perfect_reconstructibility::generator::{Generator, Parameters};
+
+	let parameters = Parameters {
+		back_edges: 0.25,
+		irreducibility: 0.5,
+		..Parameters::default()
+	};
+
+	for seed in 0..64 {
+		check_algorithms(&Generator::new(seed).run(&parameters).unwrap());
+	}
+}
@@ -0,0 +1,296 @@
+use perfect_reconstructibility::{
+	dot::{ErrorKind, Reader, Writer},
+	list::{Instruction, List},
+	nodes::{EdgeKind, Nodes, Successors, Var},
+	structurer::exit::Unifier,
+};
+
+fn read(source: &str) -> List {
+	Reader::new().run(source).unwrap()
+}
+
+fn read_error(source: &str) -> (usize, ErrorKind) {
+	let error = Reader::new().run(source).unwrap_err();
+
+	(error.line, error.kind)
+}
+
+fn write(list: &List) -> String {
+	let mut out = Vec::new();
+
+	Writer::new()
+		.run(list, list.ids().as_slice(), &mut out)
+		.unwrap();
+
+	String::from_utf8(out).unwrap()
+}
+
+fn successors(list: &List, id: usize) -> Vec<usize> {
+	list.successors(id).collect()
+}
+
+// Predecessors are listed in the order links were added, which the writer does not keep
+fn assert_same_graph(list: &List, other: &List) {
+	assert_eq!(list.len(), other.len());
+
+	for id in list.ids().ones() {
+		assert!(list.instruction(id) == other.instruction(id));
+		assert_eq!(successors(list, id), successors(other, id));
+		assert_eq!(list.edge_kinds(id), other.edge_kinds(id));
+	}
+}
+
+#[test]
+fn reads_edges_in_order() {
+	let list = read("digraph { a -> b; a -> c -> b; c -> a }");
+
+	assert_eq!(list.len(), 3);
+	assert_eq!(successors(&list, 0), [1, 2]);
+	assert!(successors(&list, 1).is_empty());
+	assert_eq!(successors(&list, 2), [1, 0]);
+}
+
+#[test]
+fn numbers_unnamed_nodes_by_first_appearance() {
+	let list = read("digraph { start -> x; y -> x; start -> y }");
+
+	assert_eq!(successors(&list, 0), [1, 2]);
+	assert_eq!(successors(&list, 2), [1]);
+}
+
+#[test]
+fn keeps_ids_in_names() {
+	let list = read("digraph { node_2 -> node_1; node_0 -> node_2; node_1 -> node_0 }");
+
+	assert_eq!(successors(&list, 0), [2]);
+	assert_eq!(successors(&list, 1), [0]);
+	assert_eq!(successors(&list, 2), [1]);
+
+	let list = read("digraph { 1 -> 0; 0 -> 1 }");
+
+	assert_eq!(successors(&list, 0), [1]);
+	assert_eq!(successors(&list, 1), [0]);
+}
+
+#[test]
+fn numbers_by_appearance_when_ids_have_gaps() {
+	let list = read("digraph { node_3 -> node_7 }");
+
+	assert_eq!(list.len(), 2);
+	assert_eq!(successors(&list, 0), [1]);
+}
+
+#[test]
+fn reads_synthetic_attributes() {
+	let list = read(
+		"digraph {
+			0 [kind=selection, var=destination];
+			1 [kind=assign, var=repetition, value=2];
+			2 [kind=nop];
+			3 [label=\"ignored\"];
+			0 -> 1 -> 2 -> 3;
+		}",
+	);
+
+	assert!(
+		*list.instruction(0)
+			== Instruction::Selection {
+				var: Var::Destination
+			}
+	);
+	assert!(
+		*list.instruction(1)
+			== Instruction::SetVariable {
+				var: Var::Repetition,
+				value: 2
+			}
+	);
+	assert!(*list.instruction(2) == Instruction::NoOperation);
+	assert!(*list.instruction(3) == Instruction::Simple);
+}
+
+#[test]
+fn skips_comments_subgraphs_and_ports() {
+	let list = read(
+		"# preprocessor line
+		strict digraph name {
+			// line comment
+			graph [rankdir = LR];
+			rankdir = TB;
+			/* block
+			   comment */
+			subgraph cluster_0 { a:n -> b:s:w }
+			{ b -> c }
+		}",
+	);
+
+	assert_eq!(list.len(), 3);
+	assert_eq!(successors(&list, 0), [1]);
+	assert_eq!(successors(&list, 1), [2]);
+}
+
+#[test]
+fn reports_errors_with_lines() {
+	assert_eq!(
+		read_error("digraph {\n\ta -> \"b\n"),
+		(2, ErrorKind::UnterminatedString)
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta -- b\n}"),
+		(2, ErrorKind::UndirectedEdge)
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta -> b"),
+		(2, ErrorKind::UnexpectedEnd)
+	);
+	assert_eq!(
+		read_error("graph { a }"),
+		(1, ErrorKind::UnexpectedToken("graph".into()))
+	);
+	assert_eq!(
+		read_error("digraph { a ! b }"),
+		(1, ErrorKind::UnexpectedToken("!".into()))
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta [kind=jump]\n}"),
+		(2, ErrorKind::UnknownKind("jump".into()))
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta -> b [kind=abnormal]\n}"),
+		(2, ErrorKind::UnknownEdgeKind("abnormal".into()))
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta [kind=selection, var=loop]\n}"),
+		(2, ErrorKind::UnknownVar("loop".into()))
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta [kind=assign, var=branch, value=x]\n}"),
+		(2, ErrorKind::InvalidValue("x".into()))
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta [kind=selection]\n}"),
+		(2, ErrorKind::MissingVar)
+	);
+	assert_eq!(
+		read_error("digraph {\n\ta [kind=assign, var=branch]\n}"),
+		(2, ErrorKind::MissingValue)
+	);
+}
+
+#[cfg(feature = "generator")]
+#[test]
+fn written_graphs_read_back() {
+	use perfect_reconstructibility::{
+		generator::{Generator, Parameters},
+		structurer::{branch, repeat},
+	};
+
+	let parameters = Parameters {
+		nodes: 32,
+		back_edges: 0.25,
+		max_degree: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..8 {
+		let mut list = Generator::new(seed).run(&parameters).unwrap();
+
+		assert_same_graph(&read(&write(&list)), &list);
+
+		let mut set = list.ids();
+
+		repeat::Bulk::new().run(&mut list, &mut set);
+		branch::Bulk::new().run(&mut list, &mut set, 0);
+
+		assert_same_graph(&read(&write(&list)), &list);
+	}
+}
+
+#[test]
+fn exit_assignments_read_and_write_back() {
+	let mut list = read("digraph { 0 -> 1; 0 -> 2; 2 [kind=simple]; }");
+	let mut set = list.ids();
+
+	Unifier::new().run(&mut list, &mut set);
+
+	let out = write(&list);
+	let other = read(&out);
+
+	assert!(
+		*other.instruction(5)
+			== Instruction::SetVariable {
+				var: Var::Exit,
+				value: 1
+			}
+	);
+	assert_same_graph(&other, &list);
+}
+
+#[test]
+fn latch_links_are_labelled_by_value() {
+	let list = read(
+		"digraph {
+			0 [kind=selection, var=repetition];
+			1 [kind=selection, var=branch];
+			0 -> 1;
+			0 -> 2;
+			1 -> 2;
+			1 -> 0;
+		}",
+	);
+	let out = write(&list);
+
+	// A latch repeats on `1`, which is its first successor
+	assert!(out.contains("node_0 -> node_1 [label=\"1\"];"));
+	assert!(out.contains("node_0 -> node_2 [label=\"0\"];"));
+	assert!(out.contains("node_1 -> node_2 [label=\"0\"];"));
+	assert!(out.contains("node_1 -> node_0 [label=\"1\"];"));
+}
+
+#[test]
+fn edge_kinds_read_and_write_back() {
+	let list = read(
+		"digraph {
+			0 -> 1;
+			1 -> 2 -> 4 [kind=pinned];
+			0 -> 2 [kind=ignored, label=\"unwind\"];
+			2 -> 3 [kind=normal];
+		}",
+	);
+
+	// Attributes apply to every edge of a chain
+	assert_eq!(list.edge_kinds(0), [EdgeKind::Normal, EdgeKind::Ignored]);
+	assert_eq!(list.edge_kind(1, 2), EdgeKind::Pinned);
+	assert_eq!(list.edge_kinds(2), [EdgeKind::Pinned, EdgeKind::Normal]);
+
+	let out = write(&list);
+
+	assert!(out.contains("node_1 -> node_2 [kind=pinned, style=bold];"));
+	assert!(out.contains("node_0 -> node_2 [label=\"1\", kind=ignored, style=dashed];"));
+	assert!(out.contains("node_2 -> node_3 [label=\"1\"];"));
+	assert_same_graph(&read(&out), &list);
+}
+
+struct Adjacency(Vec<Vec<usize>>);
+
+impl Successors for Adjacency {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.0[id].iter().copied()
+	}
+}
+
+#[test]
+fn graphs_without_synthetics_are_written() {
+	let graph = Adjacency(vec![vec![1, 2], vec![2], vec![]]);
+	let list = read("digraph { 0 -> 1; 0 -> 2; 1 -> 2 }");
+	let mut out = Vec::new();
+
+	Writer::new()
+		.run_original(&graph, list.ids().as_slice(), &mut out)
+		.unwrap();
+
+	let out = String::from_utf8(out).unwrap();
+
+	assert!(!out.contains("kind="));
+	assert_same_graph(&read(&out), &list);
+}
@@ -0,0 +1,177 @@
+use perfect_reconstructibility::{
+	emit::c,
+	interpreter::Interpreter,
+	list::List,
+	nodes::Successors,
+	pruner::Policy,
+	set::Slice,
+	structurer::{
+		branch::Branch,
+		driver::{Driver, Options},
+		observer::StructureObserver,
+	},
+	tree::Builder,
+};
+
+fn count_exits(list: &List) -> usize {
+	list.ids()
+		.ones()
+		.filter(|&id| list.successors(id).next().is_none())
+		.count()
+}
+
+// A loop with a return inside it and one after it
+const RETURNS: (usize, &[(usize, usize)]) = (5, &[(0, 1), (1, 2), (2, 1), (2, 3), (1, 4)]);
+
+const UNIFIED: Options = Options {
+	unify_exits: true,
+	unreachable: Policy::Report,
+};
+
+#[test]
+fn unified_exits_leave_one_sink() {
+	let (len, edges) = RETURNS;
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+
+	Driver::new().run(&mut list, &mut set, 0, UNIFIED).unwrap();
+
+	assert_eq!(count_exits(&list), 1);
+	assert!(Builder::new().run(&list, set.as_slice(), 0).is_ok());
+}
+
+#[test]
+fn exit_variable_does_not_shadow_libc() {
+	let (len, edges) = RETURNS;
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+
+	Driver::new().run(&mut list, &mut set, 0, UNIFIED).unwrap();
+
+	let statements = Builder::new().run(&list, set.as_slice(), 0).unwrap();
+	let mut out = String::new();
+
+	c::Emitter::new()
+		.run(&statements, &mut |id| format!("block_{id}();"), &mut out)
+		.unwrap();
+
+	assert!(out.contains("int exit_id = 0;"));
+	assert!(!out.contains("exit ="));
+}
+
+#[test]
+fn separate_exits_are_kept() {
+	let (len, edges) = RETURNS;
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+
+	Driver::new()
+		.run(&mut list, &mut set, 0, Options::default())
+		.unwrap();
+
+	assert_eq!(count_exits(&list), 2);
+}
+
+#[test]
+fn unreachable_nodes_follow_the_policy() {
+	let edges = &[(0, 1), (2, 3), (3, 2), (3, 1)];
+	let options = |unreachable| Options {
+		unify_exits: true,
+		unreachable,
+	};
+
+	let mut list = List::from_edges(4, edges);
+	let mut set = list.ids();
+	let error = Driver::new()
+		.run(&mut list, &mut set, 0, options(Policy::Report))
+		.unwrap_err();
+
+	assert_eq!(error.ids, [2, 3]);
+
+	let mut list = List::from_edges(4, edges);
+	let mut set = list.ids();
+	let mut driver = Driver::new();
+	driver
+		.run(&mut list, &mut set, 0, options(Policy::Separate))
+		.unwrap();
+
+	assert_eq!(driver.starts().len(), 2);
+	assert_eq!(driver.cut(), [(3, 1)]);
+}
+
+// Records each branch head with the original nodes of the set it is restructured in
+#[derive(Default)]
+struct Heads(Vec<(usize, Vec<usize>)>);
+
+impl<N> StructureObserver<N> for Heads {
+	fn on_branch_head(&mut self, _: &N, set: Slice, head: usize, _: &[Branch], _: &[usize]) {
+		self.0
+			.push((head, set.ones().filter(|&id| id < 9).collect()));
+	}
+}
+
+fn trace(list: &List, start: usize, choices: usize) -> Vec<usize> {
+	let mut interpreter = Interpreter::new();
+
+	interpreter
+		.run(list, start, 8, |id, count| (choices >> id) % count)
+		.unwrap();
+
+	interpreter.trace().to_vec()
+}
+
+// The diamond from 0 and the branch from 4 whose tails cross are separate regions once
+// the link from 7 to 3 is cut. Each is restructured once, from its own start and with
+// only its own nodes.
+#[test]
+fn separate_regions_are_restructured_alone() {
+	let edges = [
+		(0, 1),
+		(0, 2),
+		(1, 3),
+		(2, 3),
+		(4, 5),
+		(4, 6),
+		(5, 7),
+		(5, 8),
+		(6, 8),
+	];
+	let expected = List::from_edges(9, &edges);
+	let mut list = List::from_edges(9, &[&edges[..], &[(7, 3)]].concat());
+	let mut set = list.ids();
+	let mut heads = Heads::default();
+	let mut driver = Driver::new();
+	let options = Options {
+		unify_exits: false,
+		unreachable: Policy::Separate,
+	};
+
+	driver
+		.run_observed(&mut list, &mut set, 0, options, &mut heads)
+		.unwrap();
+
+	assert_eq!(driver.starts(), [0, 4]);
+	assert_eq!(driver.cut(), [(7, 3)]);
+
+	let mut unique: Vec<_> = heads.0.iter().map(|&(head, _)| head).collect();
+
+	unique.sort_unstable();
+	unique.dedup();
+
+	assert_eq!(unique.len(), heads.0.len(), "{:?}", heads.0);
+
+	for (head, ids) in &heads.0 {
+		let region = if *head < 4 { 0..4 } else { 4..9 };
+
+		assert!(ids.iter().all(|id| region.contains(id)), "{:?}", heads.0);
+	}
+
+	for &start in driver.starts() {
+		for choices in 0..512 {
+			assert_eq!(
+				trace(&list, start, choices),
+				trace(&expected, start, choices)
+			);
+		}
+	}
+}
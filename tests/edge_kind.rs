@@ -0,0 +1,152 @@
+use perfect_reconstructibility::{
+	directed::dominator_finder::DominatorFinder,
+	list::List,
+	nodes::{EdgeKind, Nodes, Successors},
+	set::Slice,
+	structurer::{
+		branch::{self, Branch, Single},
+		observer::StructureObserver,
+		repeat,
+		statistics::Statistics,
+	},
+};
+
+const PINNED: EdgeKind = EdgeKind::Pinned;
+const IGNORED: EdgeKind = EdgeKind::Ignored;
+
+fn from_edges(len: usize, edges: &[(usize, usize)], kinds: &[(usize, usize, EdgeKind)]) -> List {
+	let mut list = List::from_edges(len, edges);
+
+	for &(from, to, kind) in kinds {
+		list.add_link_with_kind(from, to, kind);
+	}
+
+	list
+}
+
+fn restructure<O: StructureObserver<List>>(list: &mut List, observer: &mut O) {
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run_observed(list, &mut set, observer);
+	branch::Bulk::new().run_observed(list, &mut set, 0, observer);
+}
+
+fn has_link(list: &List, from: usize, to: usize) -> bool {
+	list.successors(from).any(|id| id == to)
+}
+
+#[derive(Default)]
+struct Heads {
+	heads: Vec<usize>,
+	kept: Vec<(usize, usize)>,
+}
+
+impl<N> StructureObserver<N> for Heads {
+	fn on_branch_head(&mut self, _: &N, _: Slice, head: usize, _: &[Branch], _: &[usize]) {
+		self.heads.push(head);
+	}
+
+	fn on_links_kept(&mut self, _: &N, _: Slice, links: &[(usize, usize)]) {
+		self.kept.extend_from_slice(links);
+	}
+}
+
+#[test]
+fn normal_links_are_the_default() {
+	let list = from_edges(2, &[(0, 1)], &[]);
+
+	assert_eq!(list.edge_kind(0, 1), EdgeKind::Normal);
+	assert_eq!(list.edge_kind(1, 0), EdgeKind::Normal);
+}
+
+// A branch whose arms both reach 1, one of them through a pinned `longjmp`
+#[test]
+fn parallel_links_have_the_most_restrictive_kind() {
+	let list = from_edges(
+		3,
+		&[(0, 1), (0, 2)],
+		&[(0, 1, IGNORED), (0, 1, PINNED), (0, 2, IGNORED)],
+	);
+
+	assert_eq!(list.edge_kind(0, 1), PINNED);
+	assert_eq!(list.edge_kind(0, 2), IGNORED);
+}
+
+// A loop whose second exit is a `longjmp`
+#[test]
+fn pinned_loop_exits_are_kept() {
+	let mut list = from_edges(5, &[(0, 1), (1, 2), (2, 1), (2, 3)], &[(1, 4, PINNED)]);
+	let mut statistics = Statistics::new();
+
+	restructure(&mut list, &mut statistics);
+
+	assert!(has_link(&list, 1, 4));
+	assert_eq!(list.edge_kind(1, 4), PINNED);
+	assert_eq!(statistics.partial_regions, 1);
+}
+
+// A loop whose body may unwind to a handler
+#[test]
+fn ignored_links_are_left_out() {
+	let edges = &[(0, 1), (1, 2), (2, 1), (2, 3), (3, 5)];
+	let mut list = from_edges(6, edges, &[(1, 4, IGNORED), (4, 5, IGNORED)]);
+	let mut heads = Heads::default();
+
+	restructure(&mut list, &mut heads);
+
+	// The handler is not a second exit, so the loop is fully structured
+	assert!(has_link(&list, 1, 4));
+	assert!(heads.kept.is_empty());
+}
+
+// Every link into the tail is pinned, so the branch at node 0 is left as it is, but
+// the branch at node 5 in its tail is still restructured
+#[test]
+fn tails_of_branches_left_alone_are_restructured() {
+	let edges = &[
+		(0, 1),
+		(0, 2),
+		(1, 3),
+		(4, 5),
+		(5, 6),
+		(5, 7),
+		(6, 8),
+		(7, 8),
+	];
+	let kinds = &[(1, 4, PINNED), (2, 4, PINNED), (3, 5, PINNED)];
+	let mut list = from_edges(9, edges, kinds);
+	let mut heads = Heads::default();
+
+	restructure(&mut list, &mut heads);
+
+	assert_eq!(heads.heads, [0, 5]);
+	assert_eq!(heads.kept, [(1, 4), (2, 4), (3, 5)]);
+}
+
+// The pinned link from node 2 still reaches node 3 around the new exit, so node 3 is
+// dominated by the head while node 4 is dominated by the exit
+#[test]
+fn kept_links_keep_tail_dominators() {
+	let edges = &[(0, 1), (0, 2), (1, 3), (1, 4), (2, 4)];
+	let mut list = from_edges(5, edges, &[(2, 3, PINNED)]);
+	let set = list.ids();
+	let mut single = Single::new();
+	let exit = single.run(&mut list, set.as_slice(), 0).unwrap();
+
+	assert_eq!(single.kept(), [(2, 3)]);
+
+	let mut finder = DominatorFinder::new();
+
+	finder.run(&list, list.ids().ones(), 0);
+
+	for id in list.ids().ones() {
+		assert_eq!(
+			single.dominators().dominator(id),
+			finder.dominator(id),
+			"dominator of node {id} differs"
+		);
+	}
+
+	assert_eq!(finder.dominator(3), Some(0));
+	assert_eq!(finder.dominator(4), Some(exit));
+}
@@ -0,0 +1,164 @@
+use std::{fs, path::Path};
+
+use perfect_reconstructibility::{
+	emit::{lua, pseudo, rust, wat},
+	list::List,
+	structurer::{branch, repeat},
+	tree::{Builder, Case, Condition, Selector, Statement},
+};
+
+// A name for the golden files, the number of nodes and the edges
+type Graph = (&'static str, usize, &'static [(usize, usize)]);
+
+const GRAPHS: &[Graph] = &[
+	("diamond", 4, &[(0, 1), (0, 2), (1, 3), (2, 3)]),
+	("loop", 4, &[(0, 1), (1, 2), (2, 1), (2, 3)]),
+	(
+		"loop_exits",
+		6,
+		&[(0, 1), (1, 2), (1, 3), (2, 1), (2, 4), (3, 5), (4, 5)],
+	),
+	(
+		"irreducible",
+		5,
+		&[(0, 1), (0, 2), (1, 2), (2, 1), (1, 3), (2, 3), (3, 4)],
+	),
+	(
+		"nested",
+		7,
+		&[
+			(0, 1),
+			(1, 2),
+			(2, 3),
+			(3, 2),
+			(3, 4),
+			(2, 5),
+			(4, 1),
+			(4, 6),
+			(5, 6),
+		],
+	),
+];
+
+fn restructure(len: usize, edges: &[(usize, usize)]) -> Vec<Statement> {
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+	branch::Bulk::new().run(&mut list, &mut set, 0);
+
+	Builder::new().run(&list, set.as_slice(), 0).unwrap()
+}
+
+fn contents(id: usize) -> String {
+	format!("block_{id}();")
+}
+
+// Compares the output with `tests/golden/<name>`, or writes it there when
+// `UPDATE_GOLDEN` is set
+fn check_golden(name: &str, output: &str) {
+	let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+		.join("tests/golden")
+		.join(name);
+
+	if std::env::var_os("UPDATE_GOLDEN").is_some() {
+		fs::write(&path, output).unwrap();
+	}
+
+	let expected = fs::read_to_string(&path).unwrap();
+
+	assert!(
+		output == expected,
+		"{name} differs from the golden file:\n{output}"
+	);
+}
+
+#[test]
+fn pseudo_matches_golden() {
+	for &(name, len, edges) in GRAPHS {
+		let statements = restructure(len, edges);
+		let mut out = String::new();
+
+		pseudo::Emitter::new()
+			.run(&statements, &mut contents, &mut out)
+			.unwrap();
+
+		check_golden(&format!("{name}.txt"), &out);
+	}
+}
+
+#[test]
+fn wat_matches_golden() {
+	for &(name, len, edges) in GRAPHS {
+		let statements = restructure(len, edges);
+		let mut out = String::new();
+
+		wat::Emitter::new()
+			.run(&statements, &mut contents, &mut out)
+			.unwrap();
+
+		check_golden(&format!("{name}.wat"), &out);
+	}
+}
+
+#[test]
+fn rust_matches_golden() {
+	for &(name, len, edges) in GRAPHS {
+		let statements = restructure(len, edges);
+		let mut out = String::new();
+
+		rust::Emitter::new()
+			.run(&statements, &mut contents, &mut out)
+			.unwrap();
+
+		check_golden(&format!("{name}.rs"), &out);
+	}
+}
+
+// A node selector compared against several values is run only once
+#[test]
+fn wat_runs_shared_selectors_once() {
+	let statements = [
+		Statement::Block(0),
+		Statement::Select {
+			selector: Selector::Node(0),
+			cases: vec![
+				Case {
+					values: vec![0, 2],
+					body: vec![Statement::Block(1)],
+				},
+				Case {
+					values: vec![1],
+					body: vec![Statement::Block(2)],
+				},
+			],
+		},
+		Statement::Loop {
+			body: vec![Statement::Block(3)],
+			condition: Condition::Select {
+				selector: Selector::Node(3),
+				values: vec![1, 2],
+			},
+		},
+	];
+	let mut out = String::new();
+
+	wat::Emitter::new()
+		.run(&statements, &mut contents, &mut out)
+		.unwrap();
+
+	check_golden("shared.wat", &out);
+}
+
+#[test]
+fn lua_vars_start_at_zero() {
+	let (_, len, edges) = GRAPHS[2];
+	let statements = restructure(len, edges);
+	let mut out = String::new();
+
+	lua::Emitter::new()
+		.run(&statements, &mut contents, &mut out)
+		.unwrap();
+
+	assert_eq!(out.lines().next(), Some("local dest, rep = 0, 0"));
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "generator")]
+
+use perfect_reconstructibility::{
+	directed::dominator_finder::DominatorFinder,
+	generator::{Error, Generator, Parameters},
+	list::List,
+	nodes::{Predecessors, Successors},
+};
+
+fn generate(seed: u64, parameters: &Parameters) -> List {
+	Generator::new(seed).run(parameters).unwrap()
+}
+
+fn count_exits(list: &List) -> usize {
+	list.ids()
+		.ones()
+		.filter(|&id| list.successors(id).next().is_none())
+		.count()
+}
+
+#[test]
+fn same_seed_gives_same_graph() {
+	let parameters = Parameters {
+		back_edges: 0.25,
+		irreducibility: 0.5,
+		max_degree: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..16 {
+		assert!(generate(seed, &parameters) == generate(seed, &parameters));
+	}
+
+	assert!(generate(1, &parameters) != generate(2, &parameters));
+}
+
+#[test]
+fn graph_has_requested_exits() {
+	for exits in [1, 2, 5, 31] {
+		let parameters = Parameters {
+			nodes: 32,
+			exits,
+			..Parameters::default()
+		};
+
+		for seed in 0..16 {
+			assert_eq!(count_exits(&generate(seed, &parameters)), exits);
+		}
+	}
+}
+
+#[test]
+fn nodes_are_reached_from_entry() {
+	let parameters = Parameters {
+		back_edges: 0.5,
+		max_degree: 1,
+		exits: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..16 {
+		let list = generate(seed, &parameters);
+		let mut finder = DominatorFinder::new();
+
+		finder.run(&list, list.ids().ones(), 0);
+
+		assert_eq!(finder.ids().len(), list.len());
+		assert_eq!(list.predecessors(0).count(), 0);
+	}
+}
+
+// A graph is reducible if it has no cycle once the links to a dominator are removed.
+// The second entries added by the generator always enter a cycle.
+fn is_reducible(list: &List) -> bool {
+	let mut finder = DominatorFinder::new();
+	let mut states = vec![0_u8; list.len()];
+	let mut pending = vec![(0, false)];
+
+	finder.run(list, list.ids().ones(), 0);
+
+	while let Some((id, is_done)) = pending.pop() {
+		if is_done {
+			states[id] = 2;
+
+			continue;
+		}
+
+		if states[id] != 0 {
+			continue;
+		}
+
+		states[id] = 1;
+		pending.push((id, true));
+
+		for successor in list.successors(id) {
+			if finder.dominates(successor, id) {
+				continue;
+			}
+
+			match states[successor] {
+				0 => pending.push((successor, false)),
+				1 => return false,
+				_ => {}
+			}
+		}
+	}
+
+	true
+}
+
+#[test]
+fn irreducibility_adds_second_entries() {
+	let parameters = Parameters {
+		back_edges: 0.5,
+		irreducibility: 1.0,
+		max_degree: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..16 {
+		assert!(!is_reducible(&generate(seed, &parameters)));
+	}
+}
+
+#[test]
+fn bad_parameters_are_rejected() {
+	let run = |nodes, exits, max_degree| {
+		let parameters = Parameters {
+			nodes,
+			exits,
+			max_degree,
+			..Parameters::default()
+		};
+
+		Generator::new(0).run(&parameters).err()
+	};
+
+	assert_eq!(run(8, 0, 2), Some(Error::NoExits));
+	assert_eq!(
+		run(1, 1, 2),
+		Some(Error::TooManyExits { exits: 1, nodes: 1 })
+	);
+	assert_eq!(
+		run(8, 8, 2),
+		Some(Error::TooManyExits { exits: 8, nodes: 8 })
+	);
+	assert_eq!(run(8, 1, 0), Some(Error::NoDegree));
+	assert_eq!(run(2, 1, 1), None);
+}
@@ -0,0 +1,7 @@
+block_0();
+if select_0 == 0 {
+	block_1();
+} else {
+	block_2();
+}
+block_3();
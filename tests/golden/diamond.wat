@@ -0,0 +1,10 @@
+block_0();
+select_0
+i32.const 0
+i32.eq
+if
+	block_1();
+else
+	block_2();
+end
+block_3();
@@ -0,0 +1,34 @@
+let mut dest: usize = 0;
+let mut rep: usize = 0;
+block_0();
+if select_0 == 0 {
+	dest = 0;
+} else {
+	dest = 1;
+}
+'loop0: loop {
+	if dest == 0 {
+		block_1();
+		if select_1 == 0 {
+			dest = 1;
+			rep = 1;
+		} else {
+			dest = 0;
+			rep = 0;
+		}
+	} else {
+		block_2();
+		if select_2 == 0 {
+			dest = 0;
+			rep = 1;
+		} else {
+			dest = 1;
+			rep = 0;
+		}
+	}
+	if rep != 1 {
+		break 'loop0;
+	}
+}
+block_3();
+block_4();
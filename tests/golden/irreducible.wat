@@ -0,0 +1,57 @@
+(local $dest i32)
+(local $rep i32)
+block_0();
+select_0
+i32.const 0
+i32.eq
+if
+	i32.const 0
+	local.set $dest
+else
+	i32.const 1
+	local.set $dest
+end
+loop $loop0
+	local.get $dest
+	i32.const 0
+	i32.eq
+	if
+		block_1();
+		select_1
+		i32.const 0
+		i32.eq
+		if
+			i32.const 1
+			local.set $dest
+			i32.const 1
+			local.set $rep
+		else
+			i32.const 0
+			local.set $dest
+			i32.const 0
+			local.set $rep
+		end
+	else
+		block_2();
+		select_2
+		i32.const 0
+		i32.eq
+		if
+			i32.const 0
+			local.set $dest
+			i32.const 1
+			local.set $rep
+		else
+			i32.const 1
+			local.set $dest
+			i32.const 0
+			local.set $rep
+		end
+	end
+	local.get $rep
+	i32.const 1
+	i32.eq
+	br_if $loop0
+end
+block_3();
+block_4();
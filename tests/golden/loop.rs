@@ -0,0 +1,9 @@
+block_0();
+'loop0: loop {
+	block_1();
+	block_2();
+	if select_2 != 0 {
+		break 'loop0;
+	}
+}
+block_3();
@@ -0,0 +1,10 @@
+block_0();
+loop $loop0
+	block_1();
+	block_2();
+	select_2
+	i32.const 0
+	i32.eq
+	br_if $loop0
+end
+block_3();
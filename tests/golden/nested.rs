@@ -0,0 +1,45 @@
+let mut dest: usize = 0;
+let mut rep: usize = 0;
+block_0();
+'loop0: loop {
+	block_1();
+	'loop1: loop {
+		block_2();
+		if select_2 == 0 {
+			block_3();
+			if select_3 == 0 {
+				dest = 0;
+				rep = 1;
+			} else {
+				dest = 1;
+				rep = 0;
+			}
+		} else {
+			dest = 0;
+			rep = 0;
+		}
+		if rep != 1 {
+			break 'loop1;
+		}
+	}
+	if dest == 0 {
+		dest = 0;
+		rep = 0;
+	} else {
+		block_4();
+		if select_4 == 0 {
+			dest = 0;
+			rep = 1;
+		} else {
+			dest = 1;
+			rep = 0;
+		}
+	}
+	if rep != 1 {
+		break 'loop0;
+	}
+}
+if dest == 0 {
+	block_5();
+}
+block_6();
@@ -0,0 +1,74 @@
+(local $dest i32)
+(local $rep i32)
+block_0();
+loop $loop0
+	block_1();
+	loop $loop1
+		block_2();
+		select_2
+		i32.const 0
+		i32.eq
+		if
+			block_3();
+			select_3
+			i32.const 0
+			i32.eq
+			if
+				i32.const 0
+				local.set $dest
+				i32.const 1
+				local.set $rep
+			else
+				i32.const 1
+				local.set $dest
+				i32.const 0
+				local.set $rep
+			end
+		else
+			i32.const 0
+			local.set $dest
+			i32.const 0
+			local.set $rep
+		end
+		local.get $rep
+		i32.const 1
+		i32.eq
+		br_if $loop1
+	end
+	local.get $dest
+	i32.const 0
+	i32.eq
+	if
+		i32.const 0
+		local.set $dest
+		i32.const 0
+		local.set $rep
+	else
+		block_4();
+		select_4
+		i32.const 0
+		i32.eq
+		if
+			i32.const 0
+			local.set $dest
+			i32.const 1
+			local.set $rep
+		else
+			i32.const 1
+			local.set $dest
+			i32.const 0
+			local.set $rep
+		end
+	end
+	local.get $rep
+	i32.const 1
+	i32.eq
+	br_if $loop0
+end
+local.get $dest
+i32.const 0
+i32.eq
+if
+	block_5();
+end
+block_6();
@@ -0,0 +1,29 @@
+(local $select i32)
+block_0();
+select_0
+local.set $select
+local.get $select
+i32.const 0
+i32.eq
+local.get $select
+i32.const 2
+i32.eq
+i32.or
+if
+	block_1();
+else
+	block_2();
+end
+loop $loop0
+	block_3();
+	select_3
+	local.set $select
+	local.get $select
+	i32.const 1
+	i32.eq
+	local.get $select
+	i32.const 2
+	i32.eq
+	i32.or
+	br_if $loop0
+end
@@ -0,0 +1,85 @@
+use perfect_reconstructibility::{
+	list::List,
+	merge::{Checker, Construct, Error, Header},
+	set::Set,
+	tree::Builder,
+};
+
+fn check(list: &List, headers: &[Header]) -> Result<(), Error> {
+	Checker::new().run(list, list.ids().as_slice(), 0, headers)
+}
+
+const fn selection(id: usize, merge: usize) -> Header {
+	Header {
+		id,
+		construct: Construct::Selection,
+		merge: Some(merge),
+	}
+}
+
+#[test]
+fn built_headers_pass() {
+	let graphs: [(usize, &[(usize, usize)]); 2] = [
+		(4, &[(0, 1), (0, 2), (1, 3), (2, 3)]),
+		(5, &[(0, 1), (1, 2), (1, 3), (2, 3), (3, 1), (3, 4)]),
+	];
+
+	for (len, edges) in graphs {
+		let list = List::from_edges(len, edges);
+		let mut builder = Builder::new();
+
+		builder.run(&list, list.ids().as_slice(), 0).unwrap();
+
+		assert_eq!(check(&list, builder.headers()), Ok(()));
+	}
+}
+
+#[test]
+fn broken_rules_are_reported() {
+	let diamond = List::from_edges(4, &[(0, 1), (0, 2), (1, 3), (2, 3)]);
+
+	assert_eq!(
+		check(&diamond, &[selection(1, 3)]),
+		Err(Error::NotDominated { header: 1, id: 3 })
+	);
+	assert_eq!(
+		check(&diamond, &[selection(0, 3), selection(0, 3)]),
+		Err(Error::SharedMerge(3))
+	);
+
+	let repeat = List::from_edges(3, &[(0, 1), (1, 1), (1, 2)]);
+
+	assert_eq!(
+		check(
+			&repeat,
+			&[Header {
+				id: 1,
+				construct: Construct::Loop { continue_target: 1 },
+				merge: None,
+			}]
+		),
+		Err(Error::MissingMerge(1))
+	);
+	assert_eq!(check(&repeat, &[]), Err(Error::BackEdge { from: 1, to: 1 }));
+}
+
+// Nodes outside the set or not reached from the start are left out
+#[test]
+fn unreached_nodes_are_skipped() {
+	let list = List::from_edges(5, &[(0, 1), (0, 2), (1, 3), (2, 3), (4, 3), (3, 0)]);
+	let set: Set = [0, 1, 2].into_iter().collect();
+	let headers = [selection(0, 3), selection(4, 3), selection(0, 9)];
+
+	assert_eq!(
+		Checker::new().run(&list, set.as_slice(), 0, &headers[..1]),
+		Ok(())
+	);
+	assert_eq!(
+		Checker::new().run(&list, list.ids().as_slice(), 0, &headers[1..2]),
+		Err(Error::BackEdge { from: 3, to: 0 })
+	);
+	assert_eq!(
+		Checker::new().run(&list, set.as_slice(), 0, &headers[2..]),
+		Ok(())
+	);
+}
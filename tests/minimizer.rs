@@ -0,0 +1,42 @@
+use perfect_reconstructibility::{list::List, minimizer::Minimizer, nodes::Successors};
+
+// Removing node 4 also removes the nodes only it reaches, so the graph shrinks past
+// the nodes left to try
+#[test]
+fn removals_can_shrink_past_remaining_nodes() {
+	let list = List::from_edges(5, &[(0, 1), (0, 4), (4, 2), (2, 3)]);
+	let minimized = Minimizer::new().run(&list, |_| true);
+
+	assert_eq!(minimized.len(), 1);
+	assert_eq!(minimized.successors(0).count(), 0);
+}
+
+#[test]
+fn failure_is_kept() {
+	let list = List::from_edges(
+		8,
+		&[
+			(0, 1),
+			(1, 2),
+			(2, 3),
+			(3, 1),
+			(3, 4),
+			(4, 5),
+			(5, 4),
+			(5, 6),
+			(6, 7),
+		],
+	);
+	let has_cycle = |list: &List| {
+		list.ids()
+			.ones()
+			.any(|id| list.successors(id).any(|successor| successor <= id))
+	};
+
+	let mut minimizer = Minimizer::new();
+	let minimized = minimizer.run(&list, has_cycle);
+
+	assert!(has_cycle(&minimized));
+	assert!(minimized.len() < list.len());
+	assert!(minimizer.tries() > 0);
+}
@@ -0,0 +1,123 @@
+use perfect_reconstructibility::{
+	list::{Instruction, List},
+	structurer::{
+		branch,
+		provenance::{Provenance, Role},
+		repeat,
+	},
+};
+
+fn restructure(list: &mut List) -> Provenance {
+	let mut set = list.ids();
+	let mut provenance = Provenance::new();
+
+	repeat::Bulk::new().run_observed(list, &mut set, &mut provenance);
+	branch::Bulk::new().run_observed(list, &mut set, 0, &mut provenance);
+
+	provenance
+}
+
+// Every synthetic node has an origin, and every edge end is either an original node or
+// a node that stands in for no single edge
+fn check_origins(len: usize, edges: &[(usize, usize)]) {
+	let mut list = List::from_edges(len, edges);
+	let provenance = restructure(&mut list);
+	let is_resolved = |id: usize| {
+		id < len
+			|| provenance
+				.origin(id)
+				.is_some_and(|origin| origin.edge.is_none())
+	};
+
+	for id in len..list.len() {
+		let origin = provenance.origin(id).expect("synthetic node has no origin");
+
+		let Some((from, to)) = origin.edge else {
+			continue;
+		};
+
+		assert!(
+			is_resolved(from) && is_resolved(to),
+			"edge of node {id} is not resolved"
+		);
+
+		if from < len && to < len {
+			assert!(
+				edges.contains(&(from, to)),
+				"node {id} has no original edge"
+			);
+		}
+	}
+
+	assert!(provenance.iter().all(|(id, _)| id >= len));
+}
+
+// A loop with two entries and two exits
+const LOOP: (usize, &[(usize, usize)]) = (
+	6,
+	&[
+		(0, 1),
+		(0, 2),
+		(1, 2),
+		(2, 1),
+		(1, 3),
+		(2, 4),
+		(3, 5),
+		(4, 5),
+	],
+);
+
+// A branch with two tail nodes
+const BRANCH: (usize, &[(usize, usize)]) =
+	(6, &[(0, 1), (0, 2), (0, 3), (1, 5), (2, 4), (3, 4), (4, 5)]);
+
+#[test]
+fn loops_and_branches_have_origins() {
+	for (len, edges) in [LOOP, BRANCH] {
+		check_origins(len, edges);
+	}
+}
+
+#[test]
+fn selections_have_origins_without_edges() {
+	let mut roles = Vec::new();
+
+	for (len, edges) in [LOOP, BRANCH] {
+		let mut list = List::from_edges(len, edges);
+		let provenance = restructure(&mut list);
+
+		for id in len..list.len() {
+			if let Instruction::Selection { .. } = list.instruction(id) {
+				let origin = provenance.origin(id).unwrap();
+
+				assert_eq!(origin.edge, None);
+
+				roles.push(origin.role);
+			}
+		}
+	}
+
+	for role in [Role::Entry, Role::Exit, Role::Continue, Role::Tail] {
+		assert!(roles.contains(&role), "no selection for {role:?}");
+	}
+}
+
+// The tail connections of the branch at the head leave from the entry assignments of
+// the loop, which must still report the edges of the original graph
+#[test]
+fn edges_resolve_through_earlier_steps() {
+	let edges = [(0, 1), (0, 2), (1, 3), (1, 4), (2, 3), (3, 4), (3, 2)];
+	let mut list = List::from_edges(5, &edges);
+	let provenance = restructure(&mut list);
+
+	let tails: Vec<_> = provenance
+		.iter()
+		.filter(|(_, origin)| origin.role == Role::Tail)
+		.filter_map(|(_, origin)| origin.edge)
+		.collect();
+
+	assert!(!tails.is_empty());
+	assert!(tails.iter().all(|&(from, _)| from < 5));
+
+	check_origins(5, &edges);
+}
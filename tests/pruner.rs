@@ -0,0 +1,74 @@
+use perfect_reconstructibility::{
+	list::List,
+	nodes::{Predecessors, Successors},
+	pruner::{Error, Policy, Pruner},
+};
+
+// Node 2 is dead code leading into the live exit, and nodes 3 and 4 form a cycle that
+// nothing enters
+const DEAD: (usize, &[(usize, usize)]) = (5, &[(0, 1), (2, 1), (3, 4), (4, 3), (4, 2)]);
+
+#[test]
+fn reachable_graphs_are_left_alone() {
+	let mut list = List::from_edges(3, &[(0, 1), (1, 2), (2, 1)]);
+	let original = list.clone();
+	let mut set = list.ids();
+	let mut pruner = Pruner::new();
+
+	pruner.run(&mut list, &mut set, 0, Policy::Report).unwrap();
+
+	assert!(list == original);
+	assert!(pruner.cut().is_empty());
+	assert_eq!(pruner.starts(), [0]);
+}
+
+#[test]
+fn unreachable_nodes_are_reported() {
+	let (len, edges) = DEAD;
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+	let result = Pruner::new().run(&mut list, &mut set, 0, Policy::Report);
+
+	assert_eq!(result, Err(Error { ids: vec![2, 3, 4] }));
+	assert_eq!(list.predecessors(1).count(), 2);
+}
+
+#[test]
+fn pruned_nodes_lose_their_links() {
+	let (len, edges) = DEAD;
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+	let mut pruner = Pruner::new();
+
+	pruner.run(&mut list, &mut set, 0, Policy::Prune).unwrap();
+
+	assert_eq!(set.ones().collect::<Vec<_>>(), [0, 1]);
+	assert_eq!(list.predecessors(1).collect::<Vec<_>>(), [0]);
+	assert_eq!(pruner.cut().len(), 4);
+
+	for id in 2..len {
+		assert_eq!(list.successors(id).count(), 0);
+	}
+}
+
+#[test]
+fn separated_regions_get_starts() {
+	let (len, edges) = DEAD;
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+	let mut pruner = Pruner::new();
+
+	pruner
+		.run(&mut list, &mut set, 0, Policy::Separate)
+		.unwrap();
+
+	// Every unreachable node has a predecessor, so they start regions in order and the
+	// cycle is given a new start
+	let starts = pruner.starts();
+
+	assert_eq!(starts[..2], [0, 2]);
+	assert_eq!(starts.len(), 3);
+	assert_eq!(pruner.cut(), [(2, 1), (4, 2)]);
+	assert!(set[starts[2]]);
+	assert_eq!(list.successors(starts[2]).collect::<Vec<_>>(), [3]);
+}
@@ -0,0 +1,82 @@
+use perfect_reconstructibility::{
+	list::{Instruction, List},
+	nodes::{Successors, Var},
+	structurer::repeat,
+};
+
+// Exits assign their destination right before asking for the loop to end
+fn exit_destinations(list: &List) -> Vec<usize> {
+	list.ids()
+		.ones()
+		.filter_map(|id| match *list.instruction(id) {
+			Instruction::SetVariable {
+				var: Var::Destination,
+				value,
+			} => Some((id, value)),
+			_ => None,
+		})
+		.filter(|&(id, _)| {
+			list.successors(id).any(|successor| {
+				matches!(
+					list.instruction(successor),
+					Instruction::SetVariable {
+						var: Var::Repetition,
+						value: 0,
+					}
+				)
+			})
+		})
+		.map(|(_, value)| value)
+		.collect()
+}
+
+#[test]
+fn exit_edges_get_their_own_destination() {
+	// Node 2 leaves the loop to both 3 and 4, which used to share a value
+	let mut list = List::from_edges(
+		6,
+		&[
+			(0, 1),
+			(1, 2),
+			(1, 3),
+			(2, 1),
+			(2, 3),
+			(2, 4),
+			(3, 5),
+			(4, 5),
+		],
+	);
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+
+	let mut values = exit_destinations(&list);
+
+	values.sort_unstable();
+
+	assert_eq!(values, [0, 1, 2]);
+}
+
+#[test]
+fn loops_without_entries_have_nested_loops_structured() {
+	// Node 0 is only entered from the outer loop, so nothing outside enters it, while
+	// the inner loop is entered at both 1 and 2
+	let mut list = List::from_edges(4, &[(0, 1), (0, 2), (1, 2), (2, 1), (1, 3), (2, 3), (3, 0)]);
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+
+	let selections = set
+		.ones()
+		.filter(|&id| {
+			matches!(
+				list.instruction(id),
+				Instruction::Selection {
+					var: Var::Destination,
+				}
+			)
+		})
+		.count();
+
+	assert!(selections > 0);
+}
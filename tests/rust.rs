@@ -0,0 +1,244 @@
+use std::{
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+use perfect_reconstructibility::{
+	emit::{rust::Emitter, Blocks},
+	interpreter::Interpreter,
+	list::List,
+	nodes::Successors,
+	structurer::{branch, repeat},
+	tree::Builder,
+};
+
+const LIMIT: usize = 200;
+const SEEDS: [u32; 4] = [0, 1, 7, 1234];
+
+// The same tracing and choices as the C programs, kept in a state passed to the body
+const PRELUDE: &str = "struct State {
+	seed: u32,
+	visits: Vec<u32>,
+	steps: usize,
+}
+
+impl State {
+	fn trace(&mut self, id: u32) {
+		if self.steps == LIMIT {
+			std::process::exit(0);
+		}
+
+		self.steps += 1;
+		self.visits[id as usize] += 1;
+		println!(\"{id}\");
+	}
+
+	fn choose(&self, id: u32, count: u32) -> usize {
+		let visits = self.visits[id as usize];
+		let hash = (id + 1).wrapping_mul(2_654_435_761) ^ visits.wrapping_mul(40503) ^ self.seed;
+
+		(hash % count) as usize
+	}
+}
+";
+
+fn choose(id: usize, count: usize, visits: u32, seed: u32) -> usize {
+	let id = u32::try_from(id).unwrap();
+	let hash = (id + 1).wrapping_mul(2_654_435_761) ^ visits.wrapping_mul(40503) ^ seed;
+
+	usize::try_from(hash).unwrap() % count
+}
+
+struct Program<'a> {
+	list: &'a List,
+}
+
+impl Blocks for Program<'_> {
+	fn contents(&mut self, id: usize) -> String {
+		format!("state.trace({id});")
+	}
+
+	fn selector(&mut self, id: usize) -> String {
+		format!("state.choose({id}, {})", self.list.successors(id).count())
+	}
+}
+
+fn find_compiler() -> Option<String> {
+	let compiler = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+	let found = Command::new(&compiler).arg("--version").output();
+
+	found
+		.is_ok_and(|output| output.status.success())
+		.then_some(compiler)
+}
+
+// Compiles the body into a program in its own directory and returns its path
+fn compile(compiler: &str, name: &str, body: &str) -> PathBuf {
+	let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+	let source = directory.join("main.rs");
+	let program = directory.join("main");
+	let code = format!(
+		"const LIMIT: usize = {LIMIT};\n\n{PRELUDE}\nfn run(state: &mut State) {{\n{body}}}\n\nfn main() {{\n\tlet seed = std::env::args().nth(1).unwrap().parse().unwrap();\n\tlet mut state = State {{ seed, visits: vec![0; 4096], steps: 0 }};\n\n\trun(&mut state);\n}}\n"
+	);
+
+	std::fs::create_dir_all(&directory).unwrap();
+	std::fs::write(&source, code).unwrap();
+
+	let status = Command::new(compiler)
+		.args(["--edition", "2021", "--cap-lints", "allow", "-o"])
+		.arg(&program)
+		.arg(&source)
+		.status()
+		.unwrap();
+
+	assert!(status.success(), "{name} does not compile");
+
+	program
+}
+
+fn run_program(program: &Path, seed: u32) -> Vec<usize> {
+	let output = Command::new(program)
+		.arg(seed.to_string())
+		.output()
+		.unwrap();
+
+	assert!(output.status.success());
+
+	String::from_utf8(output.stdout)
+		.unwrap()
+		.lines()
+		.map(|line| line.parse().unwrap())
+		.collect()
+}
+
+fn run_interpreter(list: &List, seed: u32) -> Vec<usize> {
+	let mut interpreter = Interpreter::new();
+	let mut visits = vec![0; list.len()];
+
+	interpreter
+		.run(list, 0, LIMIT, |id, count| {
+			visits[id] += 1;
+
+			choose(id, count, visits[id], seed)
+		})
+		.unwrap();
+
+	interpreter.trace().to_vec()
+}
+
+fn write_structured(list: &List) -> String {
+	let mut restructured = list.clone();
+	let mut set = restructured.ids();
+
+	repeat::Bulk::new().run(&mut restructured, &mut set);
+	branch::Bulk::new().run(&mut restructured, &mut set, 0);
+
+	let statements = Builder::new()
+		.run(&restructured, set.as_slice(), 0)
+		.unwrap();
+	let mut out = String::new();
+
+	Emitter::new()
+		.run(&statements, &mut Program { list }, &mut out)
+		.unwrap();
+
+	out
+}
+
+fn check_program(compiler: &str, name: &str, list: &List) {
+	let program = compile(compiler, name, &write_structured(list));
+
+	for seed in SEEDS {
+		assert_eq!(
+			run_program(&program, seed),
+			run_interpreter(list, seed),
+			"{name} differs from the interpreter with seed {seed}"
+		);
+	}
+}
+
+// Returns the labels of the loops in the order they are opened
+fn find_labels(out: &str) -> Vec<&str> {
+	out.lines()
+		.filter_map(|line| line.trim_start().strip_suffix(": loop {"))
+		.collect()
+}
+
+// The loop of 2 and 3 is nested in the loop from 1 to 4, and followed by the loop of
+// 5 and 6 in the outer one, so each `break` must name the right label
+#[test]
+fn nested_loops_get_unique_labels() {
+	let list = List::from_edges(
+		9,
+		&[
+			(0, 1),
+			(1, 2),
+			(2, 3),
+			(3, 2),
+			(3, 5),
+			(5, 6),
+			(6, 5),
+			(6, 4),
+			(4, 1),
+			(4, 7),
+			(7, 8),
+		],
+	);
+	let out = write_structured(&list);
+	let mut labels = find_labels(&out);
+
+	assert_eq!(labels.len(), 3, "{out}");
+
+	labels.sort_unstable();
+	labels.dedup();
+
+	assert_eq!(labels.len(), 3, "{out}");
+
+	let Some(compiler) = find_compiler() else {
+		eprintln!("no Rust compiler found, skipping");
+
+		return;
+	};
+
+	check_program(&compiler, "rust_nested", &list);
+}
+
+// The loop from 1 to 2 is entered at both nodes and left from both
+#[test]
+fn irreducible_code_matches_interpreter() {
+	let list = List::from_edges(5, &[(0, 1), (0, 2), (1, 2), (2, 1), (1, 3), (2, 4), (3, 4)]);
+
+	let Some(compiler) = find_compiler() else {
+		eprintln!("no Rust compiler found, skipping");
+
+		return;
+	};
+
+	check_program(&compiler, "rust_irreducible", &list);
+}
+
+#[cfg(feature = "generator")]
+#[test]
+fn emitted_code_matches_interpreter() {
+	use perfect_reconstructibility::generator::{Generator, Parameters};
+
+	let Some(compiler) = find_compiler() else {
+		eprintln!("no Rust compiler found, skipping");
+
+		return;
+	};
+
+	let parameters = Parameters {
+		nodes: 24,
+		back_edges: 0.2,
+		irreducibility: 0.3,
+		max_degree: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..4 {
+		let list = Generator::new(seed).run(&parameters).unwrap();
+
+		check_program(&compiler, &format!("rust_generated_{seed}"), &list);
+	}
+}
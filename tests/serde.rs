@@ -0,0 +1,186 @@
+#![cfg(feature = "serde")]
+
+use perfect_reconstructibility::{
+	list::{Instruction, List, SCHEMA_VERSION},
+	nodes::{EdgeKind, Nodes, Successors, Var},
+	structurer::{branch, provenance::Provenance, repeat, statistics::Statistics},
+	tree::{Builder, Statement},
+};
+use serde_json::json;
+
+const FIXTURE: &str = include_str!("fixtures/loop_exits.json");
+
+// Predecessors are listed in the order links were added, which is not kept
+fn assert_same_graph(list: &List, other: &List) {
+	assert_eq!(list.len(), other.len());
+
+	for id in list.ids().ones() {
+		assert!(list.instruction(id) == other.instruction(id));
+		assert!(list.successors(id).eq(other.successors(id)));
+	}
+}
+
+// A loop with two exits, as restructured when the fixture was written
+fn restructured() -> List {
+	let mut list = List::from_edges(6, &[(0, 1), (1, 2), (1, 3), (2, 1), (2, 4), (3, 5), (4, 5)]);
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run(&mut list, &mut set);
+	branch::Bulk::new().run(&mut list, &mut set, 0);
+
+	list
+}
+
+#[test]
+fn fixture_reads_and_writes_back() {
+	let list: List = serde_json::from_str(FIXTURE).unwrap();
+
+	assert_same_graph(&list, &restructured());
+	assert_eq!(
+		serde_json::to_string_pretty(&list).unwrap(),
+		FIXTURE.trim_end()
+	);
+}
+
+#[cfg(feature = "generator")]
+#[test]
+fn generated_graphs_round_trip() {
+	use perfect_reconstructibility::generator::{Generator, Parameters};
+
+	let parameters = Parameters {
+		nodes: 32,
+		back_edges: 0.25,
+		max_degree: 3,
+		..Parameters::default()
+	};
+
+	for seed in 0..8 {
+		let list = Generator::new(seed).run(&parameters).unwrap();
+		let json = serde_json::to_string(&list).unwrap();
+
+		assert_same_graph(&serde_json::from_str(&json).unwrap(), &list);
+	}
+}
+
+#[test]
+fn lists_need_matching_version() {
+	let list = json!({ "version": SCHEMA_VERSION + 1, "nodes": [] });
+	let error = serde_json::from_value::<List>(list).unwrap_err();
+
+	assert!(error.to_string().contains("schema version"));
+
+	let list = json!({ "nodes": [] });
+
+	assert!(serde_json::from_value::<List>(list).is_err());
+}
+
+#[test]
+fn version_1_lists_are_read() {
+	let list = json!({
+		"version": 1,
+		"nodes": [
+			{ "instruction": "simple", "successors": [1] },
+			{ "instruction": "simple", "successors": [] },
+		],
+	});
+	let list = serde_json::from_value::<List>(list).unwrap();
+
+	assert_eq!(list.edge_kind(0, 1), EdgeKind::Normal);
+}
+
+#[test]
+fn edge_kinds_round_trip() {
+	let mut list = List::from_edges(3, &[(0, 1)]);
+
+	list.add_link_with_kind(0, 2, EdgeKind::Ignored);
+	list.add_link_with_kind(1, 2, EdgeKind::Pinned);
+
+	let json = serde_json::to_value(&list).unwrap();
+
+	assert_eq!(json["nodes"][0]["kinds"], json!(["normal", "ignored"]));
+	assert!(json["nodes"][2].get("kinds").is_none());
+
+	let read: List = serde_json::from_value(json).unwrap();
+
+	for id in list.ids().ones() {
+		assert_eq!(read.edge_kinds(id), list.edge_kinds(id));
+	}
+
+	let list = json!({
+		"version": SCHEMA_VERSION,
+		"nodes": [
+			{ "instruction": "simple", "successors": [1], "kinds": ["pinned", "normal"] },
+			{ "instruction": "simple", "successors": [] },
+		],
+	});
+
+	assert!(serde_json::from_value::<List>(list).is_err());
+}
+
+#[test]
+fn successors_must_exist() {
+	let list = json!({
+		"version": SCHEMA_VERSION,
+		"nodes": [{ "instruction": "simple", "successors": [1] }],
+	});
+
+	assert!(serde_json::from_value::<List>(list).is_err());
+}
+
+#[test]
+fn values_use_snake_case() {
+	assert_eq!(
+		serde_json::to_value(Var::Destination).unwrap(),
+		"destination"
+	);
+	assert_eq!(serde_json::to_value(EdgeKind::Ignored).unwrap(), "ignored");
+	assert_eq!(
+		serde_json::to_value(Instruction::SetVariable {
+			var: Var::Branch,
+			value: 2
+		})
+		.unwrap(),
+		json!({ "set_variable": { "var": "branch", "value": 2 } })
+	);
+
+	for kind in [EdgeKind::Normal, EdgeKind::Pinned, EdgeKind::Ignored] {
+		let json = serde_json::to_string(&kind).unwrap();
+
+		assert_eq!(serde_json::from_str::<EdgeKind>(&json).unwrap(), kind);
+	}
+}
+
+#[test]
+fn results_round_trip() {
+	let mut list = List::from_edges(6, &[(0, 1), (1, 2), (1, 3), (2, 1), (2, 4), (3, 5), (4, 5)]);
+	let mut set = list.ids();
+	let mut statistics = Statistics::new();
+	let mut provenance = Provenance::new();
+
+	repeat::Bulk::new().run_observed(&mut list, &mut set, &mut statistics);
+	branch::Bulk::new().run_observed(&mut list, &mut set, 0, &mut statistics);
+
+	let json = serde_json::to_string(&statistics).unwrap();
+
+	assert_eq!(
+		serde_json::from_str::<Statistics>(&json).unwrap(),
+		statistics
+	);
+
+	let statements = Builder::new().run(&list, set.as_slice(), 0).unwrap();
+	let json = serde_json::to_string(&statements).unwrap();
+
+	assert!(serde_json::from_str::<Vec<Statement>>(&json).unwrap() == statements);
+
+	let mut list = List::from_edges(6, &[(0, 1), (1, 2), (1, 3), (2, 1), (2, 4), (3, 5), (4, 5)]);
+	let mut set = list.ids();
+
+	repeat::Bulk::new().run_observed(&mut list, &mut set, &mut provenance);
+
+	assert!(provenance.iter().next().is_some());
+
+	let json = serde_json::to_string(&provenance).unwrap();
+	let read: Provenance = serde_json::from_str(&json).unwrap();
+
+	assert!(read.iter().eq(provenance.iter()));
+}
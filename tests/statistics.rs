@@ -0,0 +1,120 @@
+use perfect_reconstructibility::{
+	list::List,
+	nodes::Var,
+	structurer::{
+		branch,
+		driver::{Driver, Options},
+		repeat,
+		statistics::Statistics,
+	},
+};
+
+fn collect(len: usize, edges: &[(usize, usize)]) -> Statistics {
+	let mut list = List::from_edges(len, edges);
+	let mut set = list.ids();
+	let mut statistics = Statistics::new();
+
+	repeat::Bulk::new().run_observed(&mut list, &mut set, &mut statistics);
+	branch::Bulk::new().run_observed(&mut list, &mut set, 0, &mut statistics);
+
+	statistics
+}
+
+// Both entries and both exits get a destination, as do the links between the entries
+#[test]
+fn irreducible_loop_is_counted() {
+	let statistics = collect(4, &[(0, 1), (0, 2), (1, 2), (2, 1), (1, 3), (2, 3)]);
+
+	assert_eq!(
+		statistics,
+		Statistics {
+			loops: 1,
+			irreducible_loops: 1,
+			largest_loop: 2,
+			branch_heads: 5,
+			funnels: 0,
+			unified_exits: 0,
+			selections: 3,
+			partial_regions: 0,
+			destination_assignments: 6,
+			repetition_assignments: 4,
+			branch_assignments: 0,
+			exit_assignments: 0,
+		}
+	);
+	assert_eq!(statistics.assignments(Var::Destination), 6);
+	assert_eq!(statistics.synthetics(), 13);
+}
+
+// Both branches continue to the same node, so nothing needs to be joined
+#[test]
+fn diamond_is_counted() {
+	let statistics = collect(4, &[(0, 1), (0, 2), (1, 3), (2, 3)]);
+
+	assert_eq!(
+		statistics,
+		Statistics {
+			branch_heads: 1,
+			..Statistics::new()
+		}
+	);
+	assert_eq!(statistics.synthetics(), 0);
+}
+
+// The branches continue to both 4 and 5, which are joined by a funnel and a selection
+#[test]
+fn diamond_with_crossing_tails_is_counted() {
+	let statistics = collect(6, &[(0, 1), (0, 2), (1, 3), (1, 4), (2, 4), (3, 5), (4, 5)]);
+
+	assert_eq!(
+		statistics,
+		Statistics {
+			branch_heads: 3,
+			funnels: 1,
+			selections: 1,
+			branch_assignments: 3,
+			..Statistics::new()
+		}
+	);
+	assert_eq!(statistics.synthetics(), 5);
+}
+
+#[test]
+fn counts_are_added_to() {
+	let mut list = List::from_edges(4, &[(0, 1), (0, 2), (1, 3), (2, 3)]);
+	let mut statistics = Statistics::new();
+
+	for _ in 0..2 {
+		let mut set = list.ids();
+
+		branch::Bulk::new().run_observed(&mut list, &mut set, 0, &mut statistics);
+	}
+
+	assert_eq!(statistics.branch_heads, 2);
+}
+
+// The node joining the exits 1 and 2 is counted apart from the funnels
+#[test]
+fn unified_exits_are_not_funnels() {
+	let mut list = List::from_edges(3, &[(0, 1), (0, 2)]);
+	let mut set = list.ids();
+	let mut statistics = Statistics::new();
+	let options = Options {
+		unify_exits: true,
+		..Options::default()
+	};
+
+	Driver::new()
+		.run_observed(&mut list, &mut set, 0, options, &mut statistics)
+		.unwrap();
+
+	assert_eq!(
+		statistics,
+		Statistics {
+			branch_heads: 1,
+			unified_exits: 1,
+			exit_assignments: 2,
+			..Statistics::new()
+		}
+	);
+}
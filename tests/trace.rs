@@ -0,0 +1,61 @@
+use perfect_reconstructibility::{
+	list::List,
+	structurer::{
+		branch, repeat,
+		trace::{Step, Trace},
+	},
+};
+
+// A loop with two exits followed by a diamond
+fn traced() -> Trace {
+	let mut list = List::from_edges(6, &[(0, 1), (1, 2), (2, 1), (1, 3), (2, 4), (3, 5), (4, 5)]);
+	let mut set = list.ids();
+	let mut trace = Trace::new();
+
+	repeat::Bulk::new().run_observed(&mut list, &mut set, &mut trace);
+	branch::Bulk::new().run_observed(&mut list, &mut set, 0, &mut trace);
+
+	trace
+}
+
+#[test]
+fn records_loops_then_branches() {
+	let trace = traced();
+	let steps = trace.steps();
+
+	assert!(matches!(steps.first(), Some(Step::Loop { .. })));
+	assert!(steps.iter().any(|step| matches!(step, Step::Branch { .. })));
+
+	for index in 0..steps.len() {
+		assert!(trace.snapshot(index).is_some());
+	}
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn writes_one_json_step_per_line() {
+	let trace = traced();
+	let mut out = Vec::new();
+
+	trace.write_json(&mut out).unwrap();
+
+	let out = String::from_utf8(out).unwrap();
+	let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+	let steps = json.as_array().unwrap();
+
+	assert_eq!(steps.len(), trace.steps().len());
+	assert_eq!(out.lines().count(), steps.len() + 2);
+
+	for (step, value) in trace.steps().iter().zip(steps) {
+		let (kind, set) = match step {
+			Step::Loop { set, .. } => ("loop", set),
+			Step::Branch { set, .. } => ("branch", set),
+		};
+		let ids: Vec<usize> = set.ones().collect();
+
+		assert_eq!(value["kind"], kind);
+		assert_eq!(value["set"], serde_json::json!(ids));
+	}
+
+	assert_eq!(steps[0]["entries"], serde_json::json!([1]));
+}